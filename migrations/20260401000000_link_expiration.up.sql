@@ -0,0 +1,3 @@
+ALTER TABLE links
+ADD COLUMN expires_at TIMESTAMPTZ,
+ADD COLUMN max_clicks BIGINT CHECK (max_clicks > 0);
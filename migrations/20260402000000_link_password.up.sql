@@ -0,0 +1,2 @@
+ALTER TABLE links
+ADD COLUMN password_hash VARCHAR(255);
@@ -0,0 +1,3 @@
+ALTER TABLE links
+DROP COLUMN IF EXISTS cache_max_age,
+DROP COLUMN IF EXISTS redirect_type;
@@ -0,0 +1,6 @@
+-- One row per click batch moved from Redis into Postgres, so a batch that is
+-- retried after a crash or restart is never counted twice.
+CREATE TABLE click_flushes (
+    flush_id VARCHAR(64) PRIMARY KEY,
+    applied_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,12 @@
+CREATE TABLE click_events (
+    id BIGSERIAL PRIMARY KEY,
+    link_id BIGINT NOT NULL REFERENCES links(id) ON DELETE CASCADE,
+    clicked_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    referrer_host VARCHAR(255),
+    user_agent VARCHAR(512),
+    country CHAR(2),
+    ip_hash CHAR(64),
+    is_bot BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE INDEX idx_click_events_link_clicked_at ON click_events(link_id, clicked_at);
@@ -0,0 +1,4 @@
+ALTER TABLE users
+DROP COLUMN IF EXISTS timezone;
+
+DROP TABLE IF EXISTS link_click_hours;
@@ -0,0 +1,18 @@
+-- Clicks per link per UTC hour, so days can be cut in any timezone at query time.
+CREATE TABLE link_click_hours (
+    link_id BIGINT NOT NULL REFERENCES links(id) ON DELETE CASCADE,
+    hour TIMESTAMPTZ NOT NULL,
+    clicks INT NOT NULL DEFAULT 0,
+    PRIMARY KEY (link_id, hour)
+);
+
+CREATE INDEX idx_link_click_hours_hour ON link_click_hours(hour);
+
+-- link_analytics only knows the UTC+7 day of a click; put each day's total on its first hour.
+INSERT INTO link_click_hours (link_id, hour, clicks)
+SELECT link_id, date::TIMESTAMP AT TIME ZONE 'Asia/Ho_Chi_Minh', clicks
+FROM link_analytics
+WHERE COALESCE(clicks, 0) > 0;
+
+ALTER TABLE users
+ADD COLUMN timezone VARCHAR(64) NOT NULL DEFAULT 'Asia/Ho_Chi_Minh';
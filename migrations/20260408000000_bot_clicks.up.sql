@@ -0,0 +1,4 @@
+-- Hits from crawlers, link unfurlers, monitors and prefetches, kept apart from
+-- the human clicks in `clicks` (and in links.click_count).
+ALTER TABLE link_click_hours
+ADD COLUMN bot_clicks INTEGER NOT NULL DEFAULT 0;
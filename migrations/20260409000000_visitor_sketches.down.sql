@@ -0,0 +1,2 @@
+ALTER TABLE link_click_hours
+DROP COLUMN IF EXISTS visitors;
@@ -0,0 +1,4 @@
+-- Redis HyperLogLog of the visitor fingerprints seen by a link in that hour.
+-- Sketches are unioned in Redis to count unique visitors over any set of hours.
+ALTER TABLE link_click_hours
+ADD COLUMN visitors BYTEA;
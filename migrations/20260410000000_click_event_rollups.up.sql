@@ -0,0 +1,19 @@
+-- Raw click events past the retention period, counted per UTC hour and per
+-- breakdown value so referrer/device/browser/OS breakdowns survive pruning.
+-- Only what the breakdowns show is kept: the normalized referrer host and the
+-- browser, OS and device class parsed from the user agent, never the raw string.
+CREATE TABLE click_event_rollups (
+    link_id BIGINT NOT NULL REFERENCES links(id) ON DELETE CASCADE,
+    hour TIMESTAMPTZ NOT NULL,
+    referrer_host VARCHAR(255),
+    browser VARCHAR(32),
+    browser_version VARCHAR(16),
+    os VARCHAR(32),
+    device VARCHAR(16),
+    is_bot BOOLEAN NOT NULL DEFAULT FALSE,
+    clicks INT NOT NULL DEFAULT 0,
+    CONSTRAINT click_event_rollups_key
+        UNIQUE NULLS NOT DISTINCT (link_id, hour, referrer_host, browser, browser_version, os, device, is_bot)
+);
+
+CREATE INDEX idx_click_events_clicked_at ON click_events(clicked_at);
@@ -0,0 +1,34 @@
+-- Per-link alert rules checked by the background evaluator.
+--   click_threshold: fires once when the link's click_count reaches threshold.
+--   click_spike: fires at most once per UTC hour when the current hour's clicks
+--   exceed spike_factor times the hourly average of the previous window_hours.
+CREATE TABLE alert_rules (
+    id BIGSERIAL PRIMARY KEY,
+    owner_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    link_id BIGINT NOT NULL REFERENCES links(id) ON DELETE CASCADE,
+    kind VARCHAR(20) NOT NULL,
+    threshold BIGINT,
+    spike_factor DOUBLE PRECISION,
+    window_hours INT,
+    min_clicks INT NOT NULL DEFAULT 0,
+    webhook_url TEXT,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    last_triggered_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX idx_alert_rules_link_id ON alert_rules(link_id);
+CREATE INDEX idx_alert_rules_owner_id ON alert_rules(owner_id);
+
+-- A user's notification inbox.
+CREATE TABLE notifications (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    alert_rule_id BIGINT REFERENCES alert_rules(id) ON DELETE SET NULL,
+    link_id BIGINT REFERENCES links(id) ON DELETE CASCADE,
+    kind VARCHAR(20) NOT NULL,
+    message TEXT NOT NULL,
+    clicks BIGINT NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    read_at TIMESTAMPTZ
+);
+CREATE INDEX idx_notifications_user_id ON notifications(user_id, id DESC);
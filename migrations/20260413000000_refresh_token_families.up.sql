@@ -0,0 +1,12 @@
+-- Every refresh rotates the token: the old row is revoked and points at its
+-- replacement, and all tokens descending from one login share a family_id.
+-- Presenting a revoked token again revokes the whole family.
+ALTER TABLE refresh_tokens
+ADD COLUMN family_id CHAR(32),
+ADD COLUMN replaced_by_id BIGINT REFERENCES refresh_tokens(id) ON DELETE SET NULL;
+
+UPDATE refresh_tokens SET family_id = replace(gen_random_uuid()::TEXT, '-', '');
+
+ALTER TABLE refresh_tokens ALTER COLUMN family_id SET NOT NULL;
+
+CREATE INDEX idx_refresh_tokens_family_id ON refresh_tokens(family_id);
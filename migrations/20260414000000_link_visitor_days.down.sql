@@ -0,0 +1 @@
+DROP TABLE IF EXISTS link_visitor_days;
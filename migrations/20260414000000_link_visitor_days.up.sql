@@ -0,0 +1,8 @@
+-- Union of a link's hourly visitor sketches over one UTC day, merged at flush
+-- time so day, week and month uniques never have to load every hour.
+CREATE TABLE IF NOT EXISTS link_visitor_days (
+    link_id BIGINT NOT NULL REFERENCES links(id) ON DELETE CASCADE,
+    day DATE NOT NULL,
+    visitors BYTEA NOT NULL,
+    PRIMARY KEY (link_id, day)
+);
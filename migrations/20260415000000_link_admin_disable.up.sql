@@ -0,0 +1,4 @@
+-- Set when an admin takes a link down; only an admin restore clears it, so
+-- owners cannot undo moderation from their trash or by re-activating the link.
+ALTER TABLE links
+ADD COLUMN disabled_by_admin BOOLEAN NOT NULL DEFAULT FALSE;
@@ -29,4 +29,12 @@ pub async fn init_db(database_url: &str) -> Result<PgPool, sqlx::Error> {
         // 2. Thực hiện kết nối
         .connect(database_url)
         .await
-}
\ No newline at end of file
+}
+
+/// True when the error is a PostgreSQL unique constraint violation.
+pub fn is_unique_violation(err: &sqlx::Error) -> bool {
+    match err {
+        sqlx::Error::Database(db_err) => db_err.code().as_deref() == Some("23505"),
+        _ => false,
+    }
+}
@@ -1,5 +1,5 @@
 use utoipa::{Modify, OpenApi};
-use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
 use axum::response::Html;
 
 struct SecurityAddon;
@@ -16,6 +16,10 @@ impl Modify for SecurityAddon {
                     .build(),
             ),
         );
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-API-Key"))),
+        );
     }
 }
 
@@ -29,17 +33,50 @@ impl Modify for SecurityAddon {
         crate::handlers::user_handler::refresh_token,
         crate::handlers::user_handler::logout_user,
         crate::handlers::user_handler::get_me,
+        crate::handlers::user_handler::update_me,
         crate::handlers::link_handler::create_link,
+        crate::handlers::link_handler::create_links_bulk,
         crate::handlers::link_handler::redirect_link,
+        crate::handlers::link_handler::unlock_link,
         crate::handlers::link_handler::get_my_links,
+        crate::handlers::link_handler::get_trash,
+        crate::handlers::link_handler::restore_link,
+        crate::handlers::link_handler::update_link,
         crate::handlers::link_handler::delete_link,
         crate::handlers::link_handler::get_daily_analytics,
+        crate::handlers::link_handler::get_link_analytics,
+        crate::handlers::link_handler::get_breakdown,
+        crate::handlers::link_handler::get_link_breakdown,
+        crate::handlers::export_handler::export_links,
+        crate::handlers::export_handler::export_click_series,
+        crate::handlers::live_handler::live_clicks,
+        crate::handlers::live_handler::live_link_clicks,
+        crate::handlers::tag_handler::list_tags,
+        crate::handlers::tag_handler::create_tag,
+        crate::handlers::tag_handler::update_tag,
+        crate::handlers::tag_handler::delete_tag,
+        crate::handlers::tag_handler::get_link_tags,
+        crate::handlers::tag_handler::add_tag_to_link,
+        crate::handlers::tag_handler::remove_tag_from_link,
+        crate::handlers::tag_handler::get_tag_analytics,
+        crate::handlers::alert_handler::create_link_alert,
+        crate::handlers::alert_handler::list_link_alerts,
+        crate::handlers::alert_handler::list_alerts,
+        crate::handlers::alert_handler::delete_alert,
+        crate::handlers::alert_handler::list_notifications,
+        crate::handlers::alert_handler::mark_notification_read,
+        crate::handlers::alert_handler::mark_all_notifications_read,
+        crate::handlers::api_key_handler::create_api_key,
+        crate::handlers::api_key_handler::list_api_keys,
+        crate::handlers::api_key_handler::revoke_api_key,
         crate::handlers::admin_handler::list_users,
         crate::handlers::admin_handler::get_user_by_id,
         crate::handlers::admin_handler::soft_delete_user,
         crate::handlers::admin_handler::hard_delete_user,
         crate::handlers::admin_handler::list_links,
-        crate::handlers::admin_handler::disable_link
+        crate::handlers::admin_handler::disable_link,
+        crate::handlers::admin_handler::restore_link,
+        crate::handlers::admin_handler::preview_retention
     ),
     components(
         schemas(
@@ -51,11 +88,46 @@ impl Modify for SecurityAddon {
             crate::dtos::user::LogoutRequest,
             crate::dtos::user::LogoutResponse,
             crate::dtos::user::UserResponse,
+            crate::dtos::user::UpdateUserRequest,
             crate::dtos::link::CreateLinkRequest,
+            crate::dtos::link::BulkLinkStatus,
+            crate::dtos::link::BulkLinkResult,
+            crate::dtos::link::BulkCreateLinkResponse,
+            crate::dtos::link::UpdateLinkRequest,
+            crate::dtos::link::UnlockLinkRequest,
             crate::dtos::link::LinkResponse,
+            crate::dtos::link::LinkPageResponse,
             crate::dtos::link::DeleteLinkResponse,
             crate::dtos::link::DailyAnalyticsResponse,
+            crate::dtos::link::ClickSeriesPointResponse,
+            crate::dtos::link::PeriodComparisonResponse,
+            crate::dtos::link::LinkAnalyticsResponse,
+            crate::dtos::link::BreakdownItemResponse,
+            crate::dtos::link::BreakdownResponse,
+            crate::dtos::tag::CreateTagRequest,
+            crate::dtos::tag::UpdateTagRequest,
+            crate::dtos::tag::TagResponse,
+            crate::dtos::tag::TagAnalyticsResponse,
+            crate::dtos::tag::TagMessageResponse,
+            crate::dtos::alert::CreateAlertRequest,
+            crate::dtos::alert::AlertRuleResponse,
+            crate::dtos::alert::NotificationResponse,
+            crate::dtos::alert::NotificationListResponse,
+            crate::dtos::alert::MarkAllReadResponse,
+            crate::dtos::alert::AlertMessageResponse,
+            crate::handlers::alert_handler::NotificationsQuery,
+            crate::dtos::api_key::CreateApiKeyRequest,
+            crate::dtos::api_key::ApiKeyResponse,
+            crate::dtos::api_key::CreateApiKeyResponse,
             crate::handlers::link_handler::AnalyticsQuery,
+            crate::handlers::link_handler::LinkAnalyticsQuery,
+            crate::handlers::link_handler::BreakdownQuery,
+            crate::handlers::export_handler::LinkExportQuery,
+            crate::handlers::export_handler::ClickSeriesExportQuery,
+            crate::handlers::live_handler::LiveClicksQuery,
+            crate::handlers::admin_handler::RetentionPreviewQuery,
+            crate::dtos::retention::RetentionPreviewResponse,
+            crate::handlers::link_handler::MyLinksQuery,
             crate::handlers::health_handler::HealthResponse,
             crate::handlers::health_handler::ReadyResponse,
             crate::error::ErrorResponse
@@ -67,6 +139,9 @@ impl Modify for SecurityAddon {
         (name = "Auth", description = "Authentication endpoints"),
         (name = "Users", description = "User profile endpoints"),
         (name = "Links", description = "URL shortener endpoints"),
+        (name = "Tags", description = "Organize links with tags"),
+        (name = "Alerts", description = "Click alert rules and the notification inbox"),
+        (name = "API Keys", description = "Scoped keys for programmatic access"),
         (name = "Admin", description = "Administrative endpoints")
     )
 )]
@@ -0,0 +1,98 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use crate::models::alert::{AlertRule, Notification};
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct CreateAlertRequest {
+    /// click_threshold | click_spike
+    pub kind: String,
+    /// click_threshold: total clicks that trigger the alert.
+    pub threshold: Option<i64>,
+    /// click_spike: how many times the trailing hourly average the current hour must exceed.
+    pub spike_factor: Option<f64>,
+    /// click_spike: hours the average is taken over (1-168, default 24).
+    pub window_hours: Option<i32>,
+    /// click_spike: hours with fewer clicks never trigger (default 10).
+    pub min_clicks: Option<i32>,
+    /// Optional http(s) URL that receives a JSON POST when the alert fires.
+    pub webhook_url: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct AlertRuleResponse {
+    pub id: i64,
+    pub link_id: i64,
+    pub kind: String,
+    pub threshold: Option<i64>,
+    pub spike_factor: Option<f64>,
+    pub window_hours: Option<i32>,
+    pub min_clicks: i32,
+    pub webhook_url: Option<String>,
+    pub is_active: bool,
+    pub last_triggered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<AlertRule> for AlertRuleResponse {
+    fn from(rule: AlertRule) -> Self {
+        Self {
+            id: rule.id,
+            link_id: rule.link_id,
+            kind: rule.kind,
+            threshold: rule.threshold,
+            spike_factor: rule.spike_factor,
+            window_hours: rule.window_hours,
+            min_clicks: rule.min_clicks,
+            webhook_url: rule.webhook_url,
+            is_active: rule.is_active,
+            last_triggered_at: rule.last_triggered_at,
+            created_at: rule.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct NotificationResponse {
+    pub id: i64,
+    pub alert_rule_id: Option<i64>,
+    pub link_id: Option<i64>,
+    pub kind: String,
+    pub message: String,
+    pub clicks: i64,
+    pub created_at: DateTime<Utc>,
+    pub read_at: Option<DateTime<Utc>>,
+}
+
+impl From<Notification> for NotificationResponse {
+    fn from(notification: Notification) -> Self {
+        Self {
+            id: notification.id,
+            alert_rule_id: notification.alert_rule_id,
+            link_id: notification.link_id,
+            kind: notification.kind,
+            message: notification.message,
+            clicks: notification.clicks,
+            created_at: notification.created_at,
+            read_at: notification.read_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct NotificationListResponse {
+    pub unread_count: i64,
+    pub items: Vec<NotificationResponse>,
+    /// Pass as `before_id` to get the next, older page; null on the last page.
+    pub next_before_id: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct MarkAllReadResponse {
+    pub updated: u64,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct AlertMessageResponse {
+    pub message: String,
+}
@@ -8,4 +8,8 @@ pub struct Claims {
     pub iat: usize,
     pub exp: usize,
     pub jti: Option<String>,
+    /// Set when the request was made with an API key: what the key may do.
+    /// Login tokens carry none and are not limited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scopes: Option<Vec<String>>,
 }
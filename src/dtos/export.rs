@@ -0,0 +1,110 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use crate::models::link::Link;
+use crate::models::link_analytics::LinkClickSeriesRow;
+use crate::utils::export::ExportRow;
+
+#[derive(Serialize)]
+pub struct LinkExportRow {
+    pub id: i64,
+    pub short_code: String,
+    pub original_url: String,
+    pub title: Option<String>,
+    pub click_count: i64,
+    pub is_active: bool,
+    pub is_protected: bool,
+    pub redirect_type: i16,
+    pub max_clicks: Option<i64>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Link> for LinkExportRow {
+    fn from(link: Link) -> Self {
+        Self {
+            id: link.id,
+            short_code: link.short_code,
+            original_url: link.original_url,
+            title: link.title,
+            click_count: link.click_count.unwrap_or(0),
+            is_active: link.is_active.unwrap_or(true),
+            is_protected: link.password_hash.is_some(),
+            redirect_type: link.redirect_type,
+            max_clicks: link.max_clicks,
+            expires_at: link.expires_at,
+            created_at: link.created_at,
+            updated_at: link.updated_at,
+        }
+    }
+}
+
+impl ExportRow for LinkExportRow {
+    const CSV_HEADER: &'static [&'static str] = &[
+        "id",
+        "short_code",
+        "original_url",
+        "title",
+        "click_count",
+        "is_active",
+        "is_protected",
+        "redirect_type",
+        "max_clicks",
+        "expires_at",
+        "created_at",
+        "updated_at",
+    ];
+
+    fn csv_values(&self) -> Vec<String> {
+        vec![
+            self.id.to_string(),
+            self.short_code.clone(),
+            self.original_url.clone(),
+            self.title.clone().unwrap_or_default(),
+            self.click_count.to_string(),
+            self.is_active.to_string(),
+            self.is_protected.to_string(),
+            self.redirect_type.to_string(),
+            self.max_clicks.map(|v| v.to_string()).unwrap_or_default(),
+            self.expires_at.map(|v| v.to_rfc3339()).unwrap_or_default(),
+            self.created_at.to_rfc3339(),
+            self.updated_at.to_rfc3339(),
+        ]
+    }
+}
+
+#[derive(Serialize)]
+pub struct ClickSeriesExportRow {
+    pub link_id: i64,
+    pub short_code: String,
+    /// Local start of the bucket: YYYY-MM-DD, or YYYY-MM-DDTHH:00 for hourly buckets.
+    pub period: String,
+    pub clicks: i64,
+    pub bot_clicks: i64,
+}
+
+impl ClickSeriesExportRow {
+    pub fn new(row: LinkClickSeriesRow, period: String) -> Self {
+        Self {
+            link_id: row.link_id,
+            short_code: row.short_code,
+            period,
+            clicks: row.clicks,
+            bot_clicks: row.bot_clicks,
+        }
+    }
+}
+
+impl ExportRow for ClickSeriesExportRow {
+    const CSV_HEADER: &'static [&'static str] = &["link_id", "short_code", "period", "clicks", "bot_clicks"];
+
+    fn csv_values(&self) -> Vec<String> {
+        vec![
+            self.link_id.to_string(),
+            self.short_code.clone(),
+            self.period.clone(),
+            self.clicks.to_string(),
+            self.bot_clicks.to_string(),
+        ]
+    }
+}
@@ -1,10 +1,45 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
+use crate::models::link::Link;
 
 #[derive(Serialize, Deserialize, ToSchema)]
 pub struct CreateLinkRequest {
     pub original_url: String,
     pub title: Option<String>,
+    pub alias: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub max_clicks: Option<i64>,
+    pub password: Option<String>,
+    /// 301, 302, 307 (default) or 308.
+    pub redirect_type: Option<i16>,
+    /// Seconds browsers and proxies may cache the redirect for.
+    pub cache_max_age: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct UpdateLinkRequest {
+    pub original_url: Option<String>,
+    /// Omit to keep the title, `null` to clear it.
+    #[serde(default, deserialize_with = "explicit_null", skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, nullable)]
+    pub title: Option<Option<String>>,
+    /// `true` is refused (403) on a link an admin disabled.
+    pub is_active: Option<bool>,
+    pub redirect_type: Option<i16>,
+    /// Omit to keep the current value, `null` to fall back to the default caching.
+    #[serde(default, deserialize_with = "explicit_null", skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<i32>, nullable)]
+    pub cache_max_age: Option<Option<i32>>,
+}
+
+/// Tells a field set to `null` (`Some(None)`) apart from a missing one (`None`).
+fn explicit_null<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: serde::Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
 }
 
 #[derive(Serialize, Deserialize, ToSchema)]
@@ -14,6 +49,70 @@ pub struct LinkResponse {
     pub original_url: String,
     pub title: Option<String>,
     pub click_count: i64,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub max_clicks: Option<i64>,
+    pub is_protected: bool,
+    pub redirect_type: i16,
+    pub cache_max_age: Option<i32>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Link> for LinkResponse {
+    fn from(link: Link) -> Self {
+        Self {
+            id: link.id,
+            short_code: link.short_code,
+            original_url: link.original_url,
+            title: link.title,
+            click_count: link.click_count.unwrap_or(0),
+            expires_at: link.expires_at,
+            max_clicks: link.max_clicks,
+            is_protected: link.password_hash.is_some(),
+            redirect_type: link.redirect_type,
+            cache_max_age: link.cache_max_age,
+            is_active: link.is_active.unwrap_or(true),
+            created_at: link.created_at,
+            updated_at: link.updated_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkLinkStatus {
+    Created,
+    Existing,
+    Error,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct BulkLinkResult {
+    pub index: usize,
+    pub status: BulkLinkStatus,
+    pub link: Option<LinkResponse>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct BulkCreateLinkResponse {
+    pub created: usize,
+    pub existing: usize,
+    pub failed: usize,
+    pub results: Vec<BulkLinkResult>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct UnlockLinkRequest {
+    pub password: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct LinkPageResponse {
+    pub items: Vec<LinkResponse>,
+    pub total: i64,
+    pub next_cursor: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, ToSchema)]
@@ -23,6 +122,64 @@ pub struct DeleteLinkResponse {
 
 #[derive(Serialize, Deserialize, ToSchema)]
 pub struct DailyAnalyticsResponse {
+    /// Local day the bucket starts on (YYYY-MM-DD).
     pub date: String,
+    /// Local start of the bucket: YYYY-MM-DD, or YYYY-MM-DDTHH:00 for hourly buckets.
+    pub period: String,
+    pub total_clicks: i64,
+    /// Approximate unique human visitors (HyperLogLog); null when Redis is unavailable.
+    pub uniques: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ClickSeriesPointResponse {
+    /// Local start of the bucket: YYYY-MM-DD, or YYYY-MM-DDTHH:00 for hourly buckets.
+    pub period: String,
+    pub clicks: i64,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct PeriodComparisonResponse {
+    pub from: String,
+    pub to: String,
+    pub total_clicks: i64,
+    /// Percentage change versus the previous period; null when it had no clicks.
+    pub change_percent: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct LinkAnalyticsResponse {
+    pub link_id: i64,
+    pub short_code: String,
+    pub from: String,
+    pub to: String,
+    pub granularity: String,
+    pub timezone: String,
+    pub series: Vec<ClickSeriesPointResponse>,
+    pub total_clicks: i64,
+    pub lifetime_clicks: i64,
+    pub first_click_at: Option<DateTime<Utc>>,
+    pub last_click_at: Option<DateTime<Utc>>,
+    pub previous_period: PeriodComparisonResponse,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct BreakdownItemResponse {
+    pub label: String,
+    pub clicks: i64,
+    /// Share of `total_clicks`, in percent with two decimals.
+    pub share_percent: f64,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct BreakdownResponse {
+    /// referrers, devices, browsers or os.
+    pub dimension: String,
+    /// Set when the breakdown covers a single link.
+    pub link_id: Option<i64>,
+    pub from: String,
+    pub to: String,
+    pub timezone: String,
     pub total_clicks: i64,
+    pub items: Vec<BreakdownItemResponse>,
 }
@@ -1,3 +1,8 @@
 pub mod link;
 pub mod user;
-pub mod claims;
\ No newline at end of file
+pub mod claims;
+pub mod tag;
+pub mod export;
+pub mod retention;
+pub mod alert;
+pub mod api_key;
@@ -0,0 +1,24 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Dry run of a click retention policy: what the job would remove right now.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct RetentionPreviewResponse {
+    /// delete | anonymize
+    pub mode: String,
+    pub retain_days: i64,
+    /// Events clicked before this instant are past retention.
+    pub cutoff: DateTime<Utc>,
+    /// Whether the background job is running with the configured policy.
+    pub job_enabled: bool,
+    /// Events past retention.
+    pub expired_events: i64,
+    /// Events the policy would change: all expired events when deleting, those still holding an IP hash when anonymizing.
+    pub affected_events: i64,
+    /// At most this many rollup rows would be written (delete mode); user agents
+    /// with the same browser, OS and device share a row, so usually far fewer.
+    pub rollup_rows: i64,
+    pub links: i64,
+    pub oldest_clicked_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use crate::models::tag::Tag;
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct CreateTagRequest {
+    pub name: String,
+    pub color: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct UpdateTagRequest {
+    pub name: Option<String>,
+    pub color: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct TagResponse {
+    pub id: i64,
+    pub name: String,
+    pub color: Option<String>,
+}
+
+impl From<Tag> for TagResponse {
+    fn from(tag: Tag) -> Self {
+        Self {
+            id: tag.id,
+            name: tag.name,
+            color: tag.color,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct TagAnalyticsResponse {
+    pub tag_id: i64,
+    pub name: String,
+    pub link_count: i64,
+    pub total_clicks: i64,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct TagMessageResponse {
+    pub message: String,
+}
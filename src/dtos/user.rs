@@ -29,6 +29,8 @@ pub struct RefreshTokenRequest {
 #[derive(Serialize, Deserialize, ToSchema)]
 pub struct RefreshTokenResponse {
     pub access_token: String,
+    /// Replaces the refresh token that was sent, which no longer works.
+    pub refresh_token: String,
 }
 
 #[derive(Serialize, Deserialize, ToSchema)]
@@ -48,6 +50,14 @@ pub struct UserResponse {
     pub email: String,
     pub role: String,
     pub is_active: bool,
+    /// Default timezone for analytics (IANA name).
+    pub timezone: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct UpdateUserRequest {
+    /// IANA timezone name, e.g. `Asia/Ho_Chi_Minh` or `Europe/Berlin`.
+    pub timezone: Option<String>,
 }
 
 impl From<User> for UserResponse {
@@ -58,6 +68,7 @@ impl From<User> for UserResponse {
             email: user.email.unwrap_or_default(),
             role: user.role,
             is_active: user.is_active,
+            timezone: user.timezone,
         }
     }
 }
\ No newline at end of file
@@ -1,14 +1,17 @@
 use axum::{
     Extension,
     Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
 };
+use utoipa::ToSchema;
 
 use crate::dtos::claims::Claims;
 use crate::dtos::link::{DeleteLinkResponse, LinkResponse};
+use crate::dtos::retention::RetentionPreviewResponse;
 use crate::dtos::user::{LogoutResponse, UserResponse};
 use crate::error::{AppError, AppResult};
-use crate::services::{link_service, user_service};
+use crate::models::click_event::RetentionMode;
+use crate::services::{link_service, retention_service, user_service};
 use crate::state::AppState;
 
 #[utoipa::path(
@@ -83,16 +86,7 @@ pub async fn list_links(
         .await
         .map_err(AppError::Database)?;
 
-    let response = links
-        .into_iter()
-        .map(|link| LinkResponse {
-            id: link.id,
-            short_code: link.short_code,
-            original_url: link.original_url,
-            title: link.title,
-            click_count: link.click_count.unwrap_or(0),
-        })
-        .collect();
+    let response = links.into_iter().map(LinkResponse::from).collect();
 
     Ok(Json(response))
 }
@@ -133,6 +127,38 @@ pub async fn disable_link(
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/admin/links/{id}/restore",
+    tag = "Admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("id" = i64, Path, description = "Link ID")
+    ),
+    responses(
+        (status = 200, description = "Restored link", body = crate::dtos::link::LinkResponse),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse),
+        (status = 403, description = "Forbidden", body = crate::error::ErrorResponse),
+        (status = 404, description = "Link not found or already active", body = crate::error::ErrorResponse),
+        (status = 409, description = "Owner already has an active link for this URL", body = crate::error::ErrorResponse),
+        (status = 500, description = "Database error", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn restore_link(
+    State(state): State<AppState>,
+    Extension(_claims): Extension<Claims>,
+    Path(link_id): Path<i64>,
+) -> AppResult<Json<LinkResponse>> {
+    match link_service::admin_restore_link(&state.db, link_id).await {
+        Ok(Some(link)) => Ok(Json(LinkResponse::from(link))),
+        Ok(None) => Err(AppError::NotFound(format!("Link {} not found or already active", link_id))),
+        Err(sqlx::Error::Io(io_err)) if io_err.to_string() == "LINK_EXISTS" => Err(AppError::Conflict(
+            "Owner already has an active link for this URL".to_string(),
+        )),
+        Err(e) => Err(AppError::Database(e)),
+    }
+}
+
 #[utoipa::path(
     delete,
     path = "/admin/users/{id}",
@@ -212,3 +238,68 @@ pub async fn hard_delete_user(
         Err(e) => Err(AppError::Database(e)),
     }
 }
+
+#[derive(serde::Deserialize, ToSchema)]
+pub struct RetentionPreviewQuery {
+    pub retain_days: Option<i64>,
+    pub mode: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/analytics/retention",
+    tag = "Admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("retain_days" = Option<i64>, Query, description = "Days of raw click events to keep, 1-36500 (default: configured policy)"),
+        ("mode" = Option<String>, Query, description = "delete | anonymize (default: configured policy)")
+    ),
+    responses(
+        (status = 200, description = "Dry run of the click retention policy; nothing is changed", body = crate::dtos::retention::RetentionPreviewResponse),
+        (status = 400, description = "Invalid retain_days or mode", body = crate::error::ErrorResponse),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse),
+        (status = 403, description = "Forbidden", body = crate::error::ErrorResponse),
+        (status = 500, description = "Database error", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn preview_retention(
+    State(state): State<AppState>,
+    Extension(_claims): Extension<Claims>,
+    Query(params): Query<RetentionPreviewQuery>,
+) -> AppResult<Json<RetentionPreviewResponse>> {
+    let mut policy = state.retention.unwrap_or_default();
+    if let Some(retain_days) = params.retain_days {
+        if !(1..=retention_service::MAX_RETENTION_DAYS).contains(&retain_days) {
+            return Err(AppError::BadRequest(format!(
+                "retain_days must be 1-{}",
+                retention_service::MAX_RETENTION_DAYS
+            )));
+        }
+        policy.retain_days = retain_days;
+    }
+    if let Some(mode) = params.mode.as_deref() {
+        policy.mode = RetentionMode::parse(mode)
+            .ok_or_else(|| AppError::BadRequest("mode must be delete or anonymize".to_string()))?;
+    }
+
+    let (cutoff, candidates) = retention_service::preview_retention(&state.db, &policy)
+        .await
+        .map_err(AppError::Database)?;
+
+    let affected_events = match policy.mode {
+        RetentionMode::Delete => candidates.events,
+        RetentionMode::Anonymize => candidates.with_ip_hash,
+    };
+
+    Ok(Json(RetentionPreviewResponse {
+        mode: policy.mode.as_str().to_string(),
+        retain_days: policy.retain_days,
+        cutoff,
+        job_enabled: state.retention.is_some(),
+        expired_events: candidates.events,
+        affected_events,
+        rollup_rows: if policy.mode == RetentionMode::Delete { candidates.rollup_rows } else { 0 },
+        links: candidates.links,
+        oldest_clicked_at: candidates.oldest_clicked_at,
+    }))
+}
@@ -0,0 +1,287 @@
+use axum::{
+    Json,
+    extract::{Path, State, Query},
+    Extension,
+};
+use utoipa::ToSchema;
+use crate::error::{AppError, AppResult};
+use crate::services::alert_service;
+use crate::dtos::alert::{
+    AlertMessageResponse, AlertRuleResponse, CreateAlertRequest, MarkAllReadResponse, NotificationListResponse,
+    NotificationResponse,
+};
+use crate::dtos::claims::Claims;
+use crate::models::alert::{AlertKind, NewAlertRule};
+use crate::state::AppState;
+use crate::utils::validation::validate_url;
+use crate::utils::webhook;
+
+const DEFAULT_WINDOW_HOURS: i32 = 24;
+const MAX_WINDOW_HOURS: i32 = 168;
+const DEFAULT_SPIKE_MIN_CLICKS: i32 = 10;
+const MAX_SPIKE_FACTOR: f64 = 1000.0;
+const MAX_WEBHOOK_URL_LEN: usize = 2048;
+const DEFAULT_NOTIFICATION_LIMIT: i64 = 20;
+const MAX_NOTIFICATION_LIMIT: i64 = 100;
+
+#[derive(serde::Deserialize, ToSchema)]
+pub struct NotificationsQuery {
+    pub unread_only: Option<bool>,
+    pub before_id: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/links/{id}/alerts",
+    tag = "Alerts",
+    security(("bearer_auth" = [])),
+    params(("id" = i64, Path, description = "Link ID")),
+    request_body = CreateAlertRequest,
+    responses(
+        (status = 200, description = "Alert rule created", body = AlertRuleResponse),
+        (status = 400, description = "Invalid rule, or a webhook_url that is not a public address", body = crate::error::ErrorResponse),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse),
+        (status = 404, description = "Link not found", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn create_link_alert(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(link_id): Path<i64>,
+    Json(payload): Json<CreateAlertRequest>,
+) -> AppResult<Json<AlertRuleResponse>> {
+    let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
+
+    let rule = new_alert_rule(link_id, payload).map_err(AppError::BadRequest)?;
+    if let Some(url) = rule.webhook_url.as_deref() {
+        webhook::check_target(url).await.map_err(AppError::BadRequest)?;
+    }
+
+    match alert_service::create_alert(&state.db, user_id, &rule).await {
+        Ok(Some(rule)) => Ok(Json(AlertRuleResponse::from(rule))),
+        Ok(None) => Err(AppError::NotFound(format!("Link {} not found", link_id))),
+        Err(e) => Err(AppError::Database(e)),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/links/{id}/alerts",
+    tag = "Alerts",
+    security(("bearer_auth" = [])),
+    params(("id" = i64, Path, description = "Link ID")),
+    responses(
+        (status = 200, description = "Alert rules of the link", body = [AlertRuleResponse]),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse),
+        (status = 404, description = "Link not found", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn list_link_alerts(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(link_id): Path<i64>,
+) -> AppResult<Json<Vec<AlertRuleResponse>>> {
+    let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
+
+    match alert_service::list_link_alerts(&state.db, user_id, link_id).await {
+        Ok(Some(rules)) => Ok(Json(rules.into_iter().map(AlertRuleResponse::from).collect())),
+        Ok(None) => Err(AppError::NotFound(format!("Link {} not found", link_id))),
+        Err(e) => Err(AppError::Database(e)),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/alerts",
+    tag = "Alerts",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "All my alert rules", body = [AlertRuleResponse]),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn list_alerts(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> AppResult<Json<Vec<AlertRuleResponse>>> {
+    let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
+
+    let rules = alert_service::list_alerts(&state.db, user_id).await
+        .map_err(AppError::Database)?;
+
+    Ok(Json(rules.into_iter().map(AlertRuleResponse::from).collect()))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/alerts/{id}",
+    tag = "Alerts",
+    security(("bearer_auth" = [])),
+    params(("id" = i64, Path, description = "Alert rule ID")),
+    responses(
+        (status = 200, description = "Alert rule deleted; its notifications stay in the inbox", body = AlertMessageResponse),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse),
+        (status = 404, description = "Alert rule not found", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn delete_alert(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(rule_id): Path<i64>,
+) -> AppResult<Json<AlertMessageResponse>> {
+    let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
+
+    match alert_service::delete_alert(&state.db, user_id, rule_id).await {
+        Ok(true) => Ok(Json(AlertMessageResponse {
+            message: "Alert rule deleted".to_string(),
+        })),
+        Ok(false) => Err(AppError::NotFound(format!("Alert rule {} not found", rule_id))),
+        Err(e) => Err(AppError::Database(e)),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/notifications",
+    tag = "Alerts",
+    security(("bearer_auth" = [])),
+    params(
+        ("unread_only" = Option<bool>, Query, description = "Only unread notifications (default false)"),
+        ("before_id" = Option<i64>, Query, description = "Only notifications older than this id (next_before_id of the previous page)"),
+        ("limit" = Option<i64>, Query, description = "Page size (1-100, default 20)")
+    ),
+    responses(
+        (status = 200, description = "My notifications, newest first", body = NotificationListResponse),
+        (status = 400, description = "Invalid limit", body = crate::error::ErrorResponse),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn list_notifications(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(params): Query<NotificationsQuery>,
+) -> AppResult<Json<NotificationListResponse>> {
+    let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
+
+    let limit = params.limit.unwrap_or(DEFAULT_NOTIFICATION_LIMIT);
+    if !(1..=MAX_NOTIFICATION_LIMIT).contains(&limit) {
+        return Err(AppError::BadRequest(format!("limit must be between 1 and {}", MAX_NOTIFICATION_LIMIT)));
+    }
+
+    let (items, unread_count) = alert_service::list_notifications(
+        &state.db,
+        user_id,
+        params.unread_only.unwrap_or(false),
+        params.before_id,
+        limit,
+    )
+    .await
+    .map_err(AppError::Database)?;
+
+    let next_before_id = if items.len() as i64 == limit { items.last().map(|n| n.id) } else { None };
+
+    Ok(Json(NotificationListResponse {
+        unread_count,
+        items: items.into_iter().map(NotificationResponse::from).collect(),
+        next_before_id,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/notifications/{id}/read",
+    tag = "Alerts",
+    security(("bearer_auth" = [])),
+    params(("id" = i64, Path, description = "Notification ID")),
+    responses(
+        (status = 200, description = "Notification marked as read", body = NotificationResponse),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse),
+        (status = 404, description = "Notification not found", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn mark_notification_read(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(notification_id): Path<i64>,
+) -> AppResult<Json<NotificationResponse>> {
+    let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
+
+    match alert_service::mark_notification_read(&state.db, user_id, notification_id).await {
+        Ok(Some(notification)) => Ok(Json(NotificationResponse::from(notification))),
+        Ok(None) => Err(AppError::NotFound(format!("Notification {} not found", notification_id))),
+        Err(e) => Err(AppError::Database(e)),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/notifications/read-all",
+    tag = "Alerts",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "All my unread notifications marked as read", body = MarkAllReadResponse),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn mark_all_notifications_read(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> AppResult<Json<MarkAllReadResponse>> {
+    let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
+
+    let updated = alert_service::mark_all_notifications_read(&state.db, user_id).await
+        .map_err(AppError::Database)?;
+
+    Ok(Json(MarkAllReadResponse { updated }))
+}
+
+/// Checks the settings `kind` needs and drops the ones it does not use.
+fn new_alert_rule(link_id: i64, payload: CreateAlertRequest) -> Result<NewAlertRule, String> {
+    let kind = AlertKind::parse(payload.kind.trim())
+        .ok_or_else(|| "kind must be click_threshold or click_spike".to_string())?;
+
+    let webhook_url = payload.webhook_url.map(|url| url.trim().to_string()).filter(|url| !url.is_empty());
+    if let Some(url) = &webhook_url {
+        if url.len() > MAX_WEBHOOK_URL_LEN || !validate_url(url) {
+            return Err("webhook_url must be a valid http(s) URL".to_string());
+        }
+    }
+
+    match kind {
+        AlertKind::ClickThreshold => {
+            let threshold = payload.threshold.filter(|&t| t >= 1)
+                .ok_or_else(|| "click_threshold needs a threshold of at least 1".to_string())?;
+            Ok(NewAlertRule {
+                link_id,
+                kind,
+                threshold: Some(threshold),
+                spike_factor: None,
+                window_hours: None,
+                min_clicks: 0,
+                webhook_url,
+            })
+        }
+        AlertKind::ClickSpike => {
+            let spike_factor = payload.spike_factor.filter(|f| f.is_finite() && *f > 1.0 && *f <= MAX_SPIKE_FACTOR)
+                .ok_or_else(|| format!("click_spike needs a spike_factor above 1 and at most {}", MAX_SPIKE_FACTOR))?;
+            let window_hours = payload.window_hours.unwrap_or(DEFAULT_WINDOW_HOURS);
+            if !(1..=MAX_WINDOW_HOURS).contains(&window_hours) {
+                return Err(format!("window_hours must be between 1 and {}", MAX_WINDOW_HOURS));
+            }
+            let min_clicks = payload.min_clicks.unwrap_or(DEFAULT_SPIKE_MIN_CLICKS);
+            if min_clicks < 0 {
+                return Err("min_clicks must not be negative".to_string());
+            }
+            Ok(NewAlertRule {
+                link_id,
+                kind,
+                threshold: None,
+                spike_factor: Some(spike_factor),
+                window_hours: Some(window_hours),
+                min_clicks,
+                webhook_url,
+            })
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    Extension,
+};
+use chrono::Utc;
+use crate::error::{AppError, AppResult};
+use crate::services::api_key_service;
+use crate::dtos::api_key::{ApiKeyResponse, CreateApiKeyRequest, CreateApiKeyResponse};
+use crate::dtos::claims::Claims;
+use crate::models::api_key::ApiScope;
+use crate::state::AppState;
+
+const MAX_NAME_LEN: usize = 100;
+
+#[utoipa::path(
+    post,
+    path = "/api-keys",
+    tag = "API Keys",
+    security(("bearer_auth" = [])),
+    request_body = CreateApiKeyRequest,
+    responses(
+        (status = 200, description = "API key created; the key is only returned here", body = CreateApiKeyResponse),
+        (status = 400, description = "Invalid name, scopes or expiry", body = crate::error::ErrorResponse),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse),
+        (status = 403, description = "API keys cannot manage API keys", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn create_api_key(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<CreateApiKeyRequest>,
+) -> AppResult<Json<CreateApiKeyResponse>> {
+    let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
+
+    let name = payload.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+        return Err(AppError::BadRequest(format!("name must be 1-{} characters", MAX_NAME_LEN)));
+    }
+
+    let mut scopes = Vec::new();
+    for scope in &payload.scopes {
+        let scope = ApiScope::parse(scope.trim()).ok_or_else(|| {
+            let known: Vec<&str> = ApiScope::ALL.iter().map(ApiScope::as_str).collect();
+            AppError::BadRequest(format!("Unknown scope {:?}; expected any of {}", scope, known.join(", ")))
+        })?;
+        if !scopes.contains(&scope.as_str().to_string()) {
+            scopes.push(scope.as_str().to_string());
+        }
+    }
+    if scopes.is_empty() {
+        return Err(AppError::BadRequest("At least one scope is required".to_string()));
+    }
+
+    if payload.expires_at.is_some_and(|at| at <= Utc::now()) {
+        return Err(AppError::BadRequest("expires_at must be in the future".to_string()));
+    }
+
+    let (api_key, key) = api_key_service::create_api_key(&state.db, user_id, name, &scopes, payload.expires_at)
+        .await
+        .map_err(AppError::Database)?;
+
+    Ok(Json(CreateApiKeyResponse {
+        key,
+        api_key: ApiKeyResponse::from(api_key),
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api-keys",
+    tag = "API Keys",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "My API keys, revoked ones included", body = [ApiKeyResponse]),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse),
+        (status = 403, description = "API keys cannot manage API keys", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn list_api_keys(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> AppResult<Json<Vec<ApiKeyResponse>>> {
+    let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
+
+    let keys = api_key_service::list_api_keys(&state.db, user_id).await
+        .map_err(AppError::Database)?;
+
+    Ok(Json(keys.into_iter().map(ApiKeyResponse::from).collect()))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api-keys/{id}",
+    tag = "API Keys",
+    security(("bearer_auth" = [])),
+    params(("id" = i64, Path, description = "API key ID")),
+    responses(
+        (status = 200, description = "API key revoked", body = ApiKeyResponse),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse),
+        (status = 403, description = "API keys cannot manage API keys", body = crate::error::ErrorResponse),
+        (status = 404, description = "API key not found", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn revoke_api_key(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(key_id): Path<i64>,
+) -> AppResult<Json<ApiKeyResponse>> {
+    let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
+
+    match api_key_service::revoke_api_key(&state.db, user_id, key_id).await {
+        Ok(Some(api_key)) => Ok(Json(ApiKeyResponse::from(api_key))),
+        Ok(None) => Err(AppError::NotFound(format!("API key {} not found", key_id))),
+        Err(e) => Err(AppError::Database(e)),
+    }
+}
@@ -0,0 +1,192 @@
+use std::future::Future;
+
+use axum::{
+    body::Body,
+    extract::{Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Extension,
+};
+use chrono::Utc;
+use futures_util::{stream, Stream, StreamExt};
+use tokio::sync::mpsc;
+use utoipa::ToSchema;
+
+use crate::dtos::claims::Claims;
+use crate::dtos::export::{ClickSeriesExportRow, LinkExportRow};
+use crate::error::{AppError, AppResult};
+use crate::handlers::link_handler::{analytics_timezone, format_period, parse_date_range, parse_granularity};
+use crate::models::click_event::ClickEventFilter;
+use crate::services::link_service;
+use crate::state::AppState;
+use crate::utils::export::{self, ExportFormat, ExportRow};
+
+/// Encoded rows are sent to the client in chunks of about this many bytes.
+const EXPORT_CHUNK_BYTES: usize = 16 * 1024;
+const EXPORT_CHANNEL_CAPACITY: usize = 16;
+
+type ExportChunk = Result<String, std::io::Error>;
+
+#[derive(serde::Deserialize, ToSchema)]
+pub struct LinkExportQuery {
+    pub format: Option<String>,
+}
+
+#[derive(serde::Deserialize, ToSchema)]
+pub struct ClickSeriesExportQuery {
+    pub from: String,
+    pub to: String,
+    pub granularity: Option<String>,
+    pub tz: Option<String>,
+    pub tag_id: Option<i64>,
+    pub format: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/links/export",
+    tag = "Links",
+    security(("bearer_auth" = []), ("api_key" = [])),
+    params(("format" = Option<String>, Query, description = "csv (default) or ndjson")),
+    responses(
+        (status = 200, description = "All of the user's links, streamed", content((String = "text/csv"), (String = "application/x-ndjson"))),
+        (status = 400, description = "Invalid format", body = crate::error::ErrorResponse),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn export_links(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(params): Query<LinkExportQuery>,
+) -> AppResult<Response> {
+    let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
+    let format = parse_format(params.format.as_deref())?;
+
+    let pool = state.db.clone();
+    Ok(stream_export(format, "links", move |sender| async move {
+        let rows = link_service::stream_user_links(&pool, user_id).map(|row| row.map(LinkExportRow::from));
+        write_rows(rows, format, &sender).await;
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/links/analytics/export",
+    tag = "Links",
+    security(("bearer_auth" = []), ("api_key" = [])),
+    params(
+        ("from" = String, Query, description = "Start date (YYYY-MM-DD)"),
+        ("to" = String, Query, description = "End date (YYYY-MM-DD)"),
+        ("granularity" = Option<String>, Query, description = "hour (ranges up to 31 days), day (default, up to 731 days), week or month (up to 3653 days)"),
+        ("tz" = Option<String>, Query, description = "IANA timezone for bucket boundaries (default: the user's timezone)"),
+        ("tag_id" = Option<i64>, Query, description = "Only export links carrying this tag"),
+        ("format" = Option<String>, Query, description = "csv (default) or ndjson")
+    ),
+    responses(
+        (status = 200, description = "Clicks per link and bucket, streamed; buckets without hits are left out", content((String = "text/csv"), (String = "application/x-ndjson"))),
+        (status = 400, description = "Invalid date range, granularity, timezone or format", body = crate::error::ErrorResponse),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn export_click_series(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(params): Query<ClickSeriesExportQuery>,
+) -> AppResult<Response> {
+    let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
+    let format = parse_format(params.format.as_deref())?;
+
+    let (from_date, to_date) = parse_date_range(&params.from, &params.to)?;
+    let granularity = parse_granularity(params.granularity.as_deref(), from_date, to_date)?;
+    let timezone = analytics_timezone(&state, user_id, params.tz.as_deref()).await?;
+
+    let filter = ClickEventFilter {
+        owner_id: user_id,
+        link_id: None,
+        tag_id: params.tag_id,
+        from_date,
+        to_date,
+        timezone,
+        include_bots: true,
+    };
+
+    let pool = state.db.clone();
+    Ok(stream_export(format, "clicks", move |sender| async move {
+        let rows = link_service::stream_click_series(&pool, &filter, granularity).map(|row| {
+            row.map(|row| {
+                let period = format_period(row.period, granularity);
+                ClickSeriesExportRow::new(row, period)
+            })
+        });
+        write_rows(rows, format, &sender).await;
+    }))
+}
+
+fn parse_format(value: Option<&str>) -> AppResult<ExportFormat> {
+    ExportFormat::parse(value).ok_or_else(|| AppError::BadRequest("format must be csv or ndjson".to_string()))
+}
+
+/// Starts `produce` in the background and streams whatever it sends as the
+/// response body, so the export is never buffered in full.
+fn stream_export<F, Fut>(format: ExportFormat, name: &str, produce: F) -> Response
+where
+    F: FnOnce(mpsc::Sender<ExportChunk>) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel::<ExportChunk>(EXPORT_CHANNEL_CAPACITY);
+    tokio::spawn(produce(sender));
+
+    let body = Body::from_stream(stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|chunk| (chunk, receiver))
+    }));
+    let disposition = format!(
+        "attachment; filename=\"{}-{}.{}\"",
+        name,
+        Utc::now().format("%Y%m%d"),
+        format.extension()
+    );
+
+    (
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+/// Encodes rows into chunks and sends them until the rows run out or the client
+/// goes away. A database error mid-stream aborts the body, so the client sees a
+/// failed download rather than a silently truncated file.
+async fn write_rows<T, S>(rows: S, format: ExportFormat, sender: &mpsc::Sender<ExportChunk>)
+where
+    T: ExportRow,
+    S: Stream<Item = Result<T, sqlx::Error>>,
+{
+    let mut rows = std::pin::pin!(rows);
+    let mut chunk = export::header::<T>(format).unwrap_or_default();
+
+    while let Some(row) = rows.next().await {
+        let encoded = row
+            .map_err(std::io::Error::other)
+            .and_then(|row| export::encode(format, &row).map_err(std::io::Error::other));
+        let line = match encoded {
+            Ok(line) => line,
+            Err(e) => {
+                tracing::warn!("Export aborted: {:?}", e);
+                let _ = sender.send(Err(e)).await;
+                return;
+            }
+        };
+
+        chunk.push_str(&line);
+        if chunk.len() >= EXPORT_CHUNK_BYTES && sender.send(Ok(std::mem::take(&mut chunk))).await.is_err() {
+            return;
+        }
+    }
+
+    if !chunk.is_empty() {
+        let _ = sender.send(Ok(chunk)).await;
+    }
+}
@@ -1,29 +1,41 @@
 use axum::{
+    Form,
     Json,
-    extract::{Path, State, Query},
-    response::Redirect,
+    extract::{ConnectInfo, Path, State, Query},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    response::{Html, IntoResponse, Redirect, Response},
     Extension,
 };
 use crate::error::{AppError, AppResult};
-use crate::services::{link_service, cache_service};
-use crate::dtos::link::{CreateLinkRequest, LinkResponse, DeleteLinkResponse, DailyAnalyticsResponse};
+use crate::services::{link_service, cache_service, click_service, user_service};
+use crate::services::cache_service::CachedLink;
+use crate::services::click_event_service::ClickContext;
+use crate::services::live_click_service::{self, LiveClick};
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use crate::services::link_service::{BulkLinkOutcome, LinkInput, LinkOptions, LinkResolution};
+use crate::dtos::link::{BreakdownItemResponse, BreakdownResponse, BulkCreateLinkResponse, BulkLinkResult, BulkLinkStatus, CreateLinkRequest, LinkResponse, DeleteLinkResponse, ClickSeriesPointResponse, DailyAnalyticsResponse, LinkAnalyticsResponse, LinkPageResponse, PeriodComparisonResponse, UnlockLinkRequest, UpdateLinkRequest};
+use crate::models::click_event::{BreakdownDimension, ClickEventFilter};
+use crate::models::link_analytics::AnalyticsGranularity;
+use crate::models::link::{Link, LinkChanges, LinkCursor, LinkSearch, LinkSortField, LinkStatusFilter};
 use crate::dtos::claims::Claims;
-use chrono::NaiveDate;
+use chrono::{NaiveDate, NaiveDateTime, Utc};
 use crate::state::AppState;
 use utoipa::ToSchema;
-use crate::utils::validation::{validate_title, validate_url};
+use crate::utils::validation::{validate_alias, validate_password, validate_title, validate_url};
 // use crate::models::link::Link;
 
 #[utoipa::path(
     post,
     path = "/links",
     tag = "Links",
-    security(("bearer_auth" = [])),
+    security(("bearer_auth" = []), ("api_key" = [])),
     request_body = CreateLinkRequest,
     responses(
         (status = 200, description = "Create short link", body = LinkResponse),
         (status = 400, description = "Invalid input", body = crate::error::ErrorResponse),
-        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse)
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse),
+        (status = 409, description = "Alias already taken", body = crate::error::ErrorResponse)
     )
 )]
 pub async fn create_link(
@@ -33,25 +45,175 @@ pub async fn create_link(
 ) -> AppResult<Json<LinkResponse>> {
     let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
 
+    validate_create_link(&payload).map_err(AppError::BadRequest)?;
+
+    let input = link_input(payload);
+    let link = link_service::create_short_link(
+        &state.db,
+        state.code_strategy,
+        &input.original_url,
+        Some(user_id),
+        input.title,
+        input.options,
+    )
+    .await
+    .map_err(create_link_error)?;
+
+    Ok(Json(LinkResponse::from(link)))
+}
+
+const BULK_MAX_ITEMS: usize = 500;
+/// Each distinct password costs one bcrypt hash, so a batch may only carry a few.
+const BULK_MAX_PASSWORDS: usize = 10;
+
+#[utoipa::path(
+    post,
+    path = "/links/bulk",
+    tag = "Links",
+    security(("bearer_auth" = []), ("api_key" = [])),
+    request_body = [CreateLinkRequest],
+    responses(
+        (status = 200, description = "Per-item result of the bulk create", body = BulkCreateLinkResponse),
+        (status = 400, description = "Empty or oversized batch, or too many different passwords", body = crate::error::ErrorResponse),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn create_links_bulk(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<Vec<CreateLinkRequest>>,
+) -> AppResult<Json<BulkCreateLinkResponse>> {
+    let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
+
+    if payload.is_empty() || payload.len() > BULK_MAX_ITEMS {
+        return Err(AppError::BadRequest(format!("Bulk request must contain 1-{} links", BULK_MAX_ITEMS)));
+    }
+
+    let passwords: HashSet<&str> = payload.iter().filter_map(|item| item.password.as_deref()).collect();
+    if passwords.len() > BULK_MAX_PASSWORDS {
+        return Err(AppError::BadRequest(format!(
+            "Bulk request may use at most {} different passwords",
+            BULK_MAX_PASSWORDS
+        )));
+    }
+
+    let mut results: Vec<Option<BulkLinkResult>> = Vec::with_capacity(payload.len());
+    let mut inputs = Vec::new();
+    let mut input_indexes = Vec::new();
+    for (index, item) in payload.into_iter().enumerate() {
+        match validate_create_link(&item) {
+            Ok(()) => {
+                results.push(None);
+                inputs.push(link_input(item));
+                input_indexes.push(index);
+            }
+            Err(message) => results.push(Some(BulkLinkResult {
+                index,
+                status: BulkLinkStatus::Error,
+                link: None,
+                error: Some(message),
+            })),
+        }
+    }
+
+    let outcomes = link_service::create_short_links_bulk(&state.db, state.code_strategy, user_id, inputs)
+        .await
+        .map_err(AppError::Database)?;
+
+    for (index, outcome) in input_indexes.into_iter().zip(outcomes) {
+        let (status, link, error) = match outcome {
+            BulkLinkOutcome::Created(link) => (BulkLinkStatus::Created, Some(LinkResponse::from(link)), None),
+            BulkLinkOutcome::Existing(link) => (BulkLinkStatus::Existing, Some(LinkResponse::from(link)), None),
+            BulkLinkOutcome::Failed(e) => {
+                let message = match create_link_error(e) {
+                    AppError::Database(e) => {
+                        tracing::warn!("Bulk link item {} failed: {:?}", index, e);
+                        "Database error".to_string()
+                    }
+                    AppError::Conflict(msg) | AppError::BadRequest(msg) => msg,
+                    other => other.to_string(),
+                };
+                (BulkLinkStatus::Error, None, Some(message))
+            }
+        };
+        results[index] = Some(BulkLinkResult { index, status, link, error });
+    }
+
+    let results: Vec<BulkLinkResult> = results.into_iter().flatten().collect();
+    let count = |wanted: fn(&BulkLinkStatus) -> bool| results.iter().filter(|r| wanted(&r.status)).count();
+    Ok(Json(BulkCreateLinkResponse {
+        created: count(|s| matches!(s, BulkLinkStatus::Created)),
+        existing: count(|s| matches!(s, BulkLinkStatus::Existing)),
+        failed: count(|s| matches!(s, BulkLinkStatus::Error)),
+        results,
+    }))
+}
+
+fn validate_create_link(payload: &CreateLinkRequest) -> Result<(), String> {
     if !validate_url(&payload.original_url) {
-        return Err(AppError::BadRequest("Invalid URL (must be http/https)".to_string()));
+        return Err("Invalid URL (must be http/https)".to_string());
     }
     if let Some(title) = payload.title.as_deref() {
         if !validate_title(title) {
-            return Err(AppError::BadRequest("Title must be 1-255 characters".to_string()));
+            return Err("Title must be 1-255 characters".to_string());
         }
     }
-    
-    let link = link_service::create_short_link(&state.db, &payload.original_url, Some(user_id), payload.title).await
-        .map_err(AppError::Database)?;
+    if let Some(alias) = payload.alias.as_deref() {
+        if !validate_alias(alias) {
+            return Err("Alias must be 3-16 characters of letters, digits, '-' or '_' and not a reserved word".to_string());
+        }
+    }
+    if payload.expires_at.is_some_and(|expires_at| expires_at <= Utc::now()) {
+        return Err("expires_at must be in the future".to_string());
+    }
+    if payload.max_clicks.is_some_and(|max_clicks| max_clicks < 1) {
+        return Err("max_clicks must be at least 1".to_string());
+    }
+    if let Some(password) = payload.password.as_deref() {
+        if !validate_password(password) {
+            return Err("Password must be 8-128 characters".to_string());
+        }
+    }
+    validate_redirect_policy(payload.redirect_type, payload.cache_max_age)
+}
 
-    Ok(Json(LinkResponse {
-        id: link.id,
-        short_code: link.short_code,
-        original_url: link.original_url,
-        title: link.title,
-        click_count: link.click_count.unwrap_or(0),
-    }))
+const MAX_CACHE_MAX_AGE: i32 = 31_536_000;
+
+fn validate_redirect_policy(redirect_type: Option<i16>, cache_max_age: Option<i32>) -> Result<(), String> {
+    if redirect_type.is_some_and(|status| !link_service::REDIRECT_TYPES.contains(&status)) {
+        return Err("redirect_type must be 301, 302, 307 or 308".to_string());
+    }
+    if cache_max_age.is_some_and(|max_age| !(0..=MAX_CACHE_MAX_AGE).contains(&max_age)) {
+        return Err(format!("cache_max_age must be 0-{} seconds", MAX_CACHE_MAX_AGE));
+    }
+    Ok(())
+}
+
+fn link_input(payload: CreateLinkRequest) -> LinkInput {
+    LinkInput {
+        original_url: payload.original_url,
+        title: payload.title,
+        options: LinkOptions {
+            alias: payload.alias,
+            expires_at: payload.expires_at,
+            max_clicks: payload.max_clicks,
+            password: payload.password,
+            redirect_type: payload.redirect_type,
+            cache_max_age: payload.cache_max_age,
+        },
+    }
+}
+
+fn create_link_error(e: sqlx::Error) -> AppError {
+    match e {
+        sqlx::Error::Io(io_err) if io_err.to_string() == "ALIAS_TAKEN" => {
+            AppError::Conflict("Alias is already taken".to_string())
+        }
+        sqlx::Error::Io(io_err) if io_err.to_string() == "LINK_EXISTS" => {
+            AppError::Conflict("This URL already has an active short link; edit or delete it first".to_string())
+        }
+        e => AppError::Database(e),
+    }
 }
 
 #[utoipa::path(
@@ -60,75 +222,472 @@ pub async fn create_link(
     tag = "Links",
     params(("short_code" = String, Path, description = "Short code")),
     responses(
-        (status = 307, description = "Temporary redirect"),
-        (status = 404, description = "Short code not found", body = crate::error::ErrorResponse)
+        (status = 307, description = "Redirect; the link's redirect_type picks 301, 302, 307 or 308"),
+        (status = 200, description = "Unlock form for a password-protected link, or a preview page without the destination for bots hitting a link with max_clicks", content_type = "text/html"),
+        (status = 404, description = "Short code not found", body = crate::error::ErrorResponse),
+        (status = 410, description = "Link expired or out of clicks", body = crate::error::ErrorResponse)
     )
 )]
 pub async fn redirect_link(
     State(state): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    method: Method,
+    headers: HeaderMap,
     Path(short_code): Path<String>,
-) -> AppResult<Redirect> {
-    match cache_service::get_cached_url(&state.redis, &short_code).await {
-        Ok(Some(url)) => return Ok(Redirect::to(&url)),
+) -> AppResult<Response> {
+    // Bots still get the redirect so link previews work and their hits are counted apart,
+    // except on links with a click budget, where they only get a preview page.
+    let context = ClickContext::from_request(&method, &headers, Some(peer));
+
+    match cache_service::get_cached_link(&state.redis, &short_code).await {
+        Ok(Some(cached)) => {
+            click_service::record_click(&state.db, &state.redis, cached.link_id, context.is_bot);
+            record_visit(&state, cached.link_id, cached.owner_id, &context);
+            return redirect_response(&cached.url, cached.status, cached.max_age);
+        }
         Ok(None) => {}
         Err(e) => {
             tracing::warn!("Redis cache read error: {:?}", e);
         }
     }
 
-    match link_service::get_original_url(&state.db, &short_code).await {
-        Ok(Some(url)) => {
-            if let Err(e) = cache_service::set_cached_url(&state.redis, &short_code, &url).await {
-                tracing::warn!("Redis cache write error: {:?}", e);
+    match link_service::resolve_short_code(&state.db, &state.redis, &short_code, None, context.is_bot).await {
+        Ok(LinkResolution::Found(link)) => {
+            if link_service::is_cacheable(&link) {
+                let cached = CachedLink {
+                    link_id: link.id,
+                    owner_id: link.owner_id,
+                    url: link.original_url.clone(),
+                    status: link.redirect_type,
+                    max_age: link.cache_max_age,
+                };
+                if let Err(e) = cache_service::set_cached_link(&state.redis, &short_code, &cached, link.expires_at).await {
+                    tracing::warn!("Redis cache write error: {:?}", e);
+                }
             }
-            Ok(Redirect::to(&url))
+            record_visit(&state, link.id, link.owner_id, &context);
+            link_redirect(&link)
+        }
+        Ok(LinkResolution::Preview(link)) => {
+            record_visit(&state, link.id, link.owner_id, &context);
+            Ok(preview_page(&short_code, &link))
+        }
+        Ok(LinkResolution::Protected) => Ok(unlock_page(&short_code, None).into_response()),
+        Ok(LinkResolution::Expired) => Err(AppError::Gone(format!("Link {} has expired", short_code))),
+        Ok(LinkResolution::NotFound) => Err(AppError::NotFound(format!("Link {} not found", short_code))),
+        Err(e) => Err(AppError::Database(e)),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/{short_code}",
+    tag = "Links",
+    params(("short_code" = String, Path, description = "Short code")),
+    request_body(content = UnlockLinkRequest, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 303, description = "Password accepted, redirect to the original URL"),
+        (status = 401, description = "Wrong password, unlock form shown again", content_type = "text/html"),
+        (status = 404, description = "Short code not found", body = crate::error::ErrorResponse),
+        (status = 410, description = "Link expired or out of clicks", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn unlock_link(
+    State(state): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    method: Method,
+    headers: HeaderMap,
+    Path(short_code): Path<String>,
+    Form(payload): Form<UnlockLinkRequest>,
+) -> AppResult<Response> {
+    let context = ClickContext::from_request(&method, &headers, Some(peer));
+
+    let password = Some(payload.password.as_str());
+    match link_service::resolve_short_code(&state.db, &state.redis, &short_code, password, context.is_bot).await {
+        Ok(LinkResolution::Found(link)) => {
+            record_visit(&state, link.id, link.owner_id, &context);
+            // See Other: the browser must follow with a GET, not replay the form POST.
+            Ok(Redirect::to(&link.original_url).into_response())
+        }
+        Ok(LinkResolution::Preview(link)) => {
+            record_visit(&state, link.id, link.owner_id, &context);
+            Ok(preview_page(&short_code, &link))
+        }
+        Ok(LinkResolution::Protected) => Ok(unlock_page(&short_code, None).into_response()),
+        Ok(LinkResolution::Expired) => Err(AppError::Gone(format!("Link {} has expired", short_code))),
+        Ok(LinkResolution::NotFound) => Err(AppError::NotFound(format!("Link {} not found", short_code))),
+        Err(sqlx::Error::Io(io_err)) if io_err.to_string() == "PASSWORD_INVALID" => {
+            Ok((StatusCode::UNAUTHORIZED, unlock_page(&short_code, Some("Wrong password"))).into_response())
         }
-        Ok(None) => Err(AppError::NotFound(format!("Link {} not found", short_code))),
         Err(e) => Err(AppError::Database(e)),
     }
 }
 
+/// Logs the click event, publishes it to the owner's live stream and, for people,
+/// adds them to the link's unique visitors. The click counter itself is updated
+/// by `link_service::resolve_short_code` or `click_service::record_click`.
+fn record_visit(state: &AppState, link_id: i64, owner_id: Option<i64>, context: &ClickContext) {
+    state.click_events.record(link_id, context);
+    if let Some(owner_id) = owner_id {
+        live_click_service::publish(&state.redis, owner_id, LiveClick::new(link_id, context));
+    }
+    if !context.is_bot {
+        if let Some(fingerprint) = state.click_events.visitor_fingerprint(context) {
+            click_service::record_visitor(&state.redis, link_id, fingerprint);
+        }
+    }
+}
+
+/// Redirect using the link's own status code and Cache-Control policy.
+fn link_redirect(link: &Link) -> AppResult<Response> {
+    let mut response = redirect_response(&link.original_url, link.redirect_type, link.cache_max_age)?;
+    if link.max_clicks.is_some() {
+        // Every visit has to reach the server to be counted against the click budget.
+        response.headers_mut().insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    }
+    Ok(response)
+}
+
+fn redirect_response(url: &str, status: i16, max_age: Option<i32>) -> AppResult<Response> {
+    let status = u16::try_from(status)
+        .ok()
+        .and_then(|status| StatusCode::from_u16(status).ok())
+        .filter(StatusCode::is_redirection)
+        .unwrap_or(StatusCode::TEMPORARY_REDIRECT);
+    let location = HeaderValue::try_from(url)
+        .map_err(|_| AppError::Internal("Stored URL is not a valid Location header".to_string()))?;
+
+    let mut response = (status, [(header::LOCATION, location)]).into_response();
+    if let Some(max_age) = max_age {
+        let cache_control = HeaderValue::from_str(&format!("public, max-age={}", max_age))
+            .expect("max-age header is ASCII");
+        response.headers_mut().insert(header::CACHE_CONTROL, cache_control);
+    }
+    Ok(response)
+}
+
+/// What crawlers and unfurlers get for a link with a click budget: the title
+/// for the preview card, but no destination and nothing they may cache.
+fn preview_page(short_code: &str, link: &Link) -> Response {
+    let short_code = escape_html(short_code);
+    let title = escape_html(link.title.as_deref().unwrap_or("Short link"));
+
+    let page = Html(format!(
+        r#"<!doctype html>
+<html lang="en">
+<head>
+    <meta charset="utf-8" />
+    <meta name="robots" content="noindex" />
+    <title>{title}</title>
+    <meta property="og:title" content="{title}" />
+    <meta property="og:description" content="Open /{short_code} in a browser to continue." />
+</head>
+<body>
+    <h1>{title}</h1>
+    <p>Open /{short_code} in a browser to continue.</p>
+</body>
+</html>
+"#
+    ));
+    ([(header::CACHE_CONTROL, HeaderValue::from_static("no-store"))], page).into_response()
+}
+
+fn unlock_page(short_code: &str, error: Option<&str>) -> Html<String> {
+    let short_code = escape_html(short_code);
+    let error = error
+        .map(|msg| format!(r#"<p class="error">{}</p>"#, escape_html(msg)))
+        .unwrap_or_default();
+
+    Html(format!(
+        r#"<!doctype html>
+<html lang="en">
+<head>
+    <meta charset="utf-8" />
+    <meta name="viewport" content="width=device-width, initial-scale=1" />
+    <meta name="robots" content="noindex" />
+    <title>Protected link</title>
+    <style>
+        body {{ font-family: "Segoe UI", sans-serif; display: grid; place-items: center; min-height: 100vh; margin: 0; background: #f7f7f2; }}
+        form {{ background: #fff; border: 1px solid #bdd0bf; border-radius: 12px; padding: 24px; width: min(360px, 90vw); }}
+        input, button {{ width: 100%; box-sizing: border-box; padding: 10px; margin-top: 12px; font-size: 15px; }}
+        .error {{ color: #b3261e; }}
+    </style>
+</head>
+<body>
+    <form method="post" action="/{short_code}">
+        <h1>Protected link</h1>
+        <p>This link is protected. Enter the password to continue.</p>
+        {error}
+        <input type="password" name="password" autocomplete="current-password" required autofocus />
+        <button type="submit">Unlock</button>
+    </form>
+</body>
+</html>
+"#
+    ))
+}
+
+fn escape_html(input: &str) -> String {
+    input
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+const MY_LINKS_DEFAULT_LIMIT: i64 = 20;
+const MY_LINKS_MAX_LIMIT: i64 = 100;
+
+#[derive(serde::Deserialize, ToSchema)]
+pub struct MyLinksQuery {
+    pub q: Option<String>,
+    pub status: Option<String>,
+    pub created_from: Option<String>,
+    pub created_to: Option<String>,
+    pub tag_id: Option<i64>,
+    pub sort: Option<String>,
+    pub order: Option<String>,
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
 #[utoipa::path(
     get,
     path = "/links/my-links",
     tag = "Links",
-    security(("bearer_auth" = [])),
+    security(("bearer_auth" = []), ("api_key" = [])),
+    params(
+        ("q" = Option<String>, Query, description = "Search in original_url, title and short_code"),
+        ("status" = Option<String>, Query, description = "all (default), active or inactive"),
+        ("created_from" = Option<String>, Query, description = "Created on or after (YYYY-MM-DD)"),
+        ("created_to" = Option<String>, Query, description = "Created on or before (YYYY-MM-DD)"),
+        ("tag_id" = Option<i64>, Query, description = "Only links carrying this tag"),
+        ("sort" = Option<String>, Query, description = "created (default), updated or clicks"),
+        ("order" = Option<String>, Query, description = "desc (default) or asc"),
+        ("cursor" = Option<String>, Query, description = "next_cursor from the previous page"),
+        ("limit" = Option<i64>, Query, description = "Page size, 1-100 (default 20)")
+    ),
     responses(
-        (status = 200, description = "List my links", body = [LinkResponse]),
+        (status = 200, description = "One page of my links", body = LinkPageResponse),
+        (status = 400, description = "Invalid query parameter", body = crate::error::ErrorResponse),
         (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse)
     )
 )]
 pub async fn get_my_links(
     State(state): State<AppState>,
     Extension(claims): Extension<Claims>,
+    Query(params): Query<MyLinksQuery>,
+) -> AppResult<Json<LinkPageResponse>> {
+    let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
+
+    let search = parse_link_search(params)?;
+
+    let (links, total, next_cursor) = link_service::search_user_links(&state.db, user_id, search).await
+        .map_err(AppError::Database)?;
+
+    Ok(Json(LinkPageResponse {
+        items: links.into_iter().map(LinkResponse::from).collect(),
+        total,
+        next_cursor: next_cursor.map(|cursor| format!("{}_{}", cursor.sort_value, cursor.id)),
+    }))
+}
+
+fn parse_link_search(params: MyLinksQuery) -> AppResult<LinkSearch> {
+    let status = match params.status.as_deref() {
+        None | Some("all") => LinkStatusFilter::All,
+        Some("active") => LinkStatusFilter::Active,
+        Some("inactive") => LinkStatusFilter::Inactive,
+        Some(_) => return Err(AppError::BadRequest("status must be all, active or inactive".to_string())),
+    };
+    let sort = match params.sort.as_deref() {
+        None | Some("created") => LinkSortField::Created,
+        Some("updated") => LinkSortField::Updated,
+        Some("clicks") => LinkSortField::Clicks,
+        Some(_) => return Err(AppError::BadRequest("sort must be created, updated or clicks".to_string())),
+    };
+    let descending = match params.order.as_deref() {
+        None | Some("desc") => true,
+        Some("asc") => false,
+        Some(_) => return Err(AppError::BadRequest("order must be asc or desc".to_string())),
+    };
+
+    let limit = params.limit.unwrap_or(MY_LINKS_DEFAULT_LIMIT);
+    if !(1..=MY_LINKS_MAX_LIMIT).contains(&limit) {
+        return Err(AppError::BadRequest(format!("limit must be 1-{}", MY_LINKS_MAX_LIMIT)));
+    }
+
+    let parse_date = |value: Option<&str>, field: &str| -> AppResult<Option<NaiveDate>> {
+        value
+            .map(|v| {
+                NaiveDate::parse_from_str(v, "%Y-%m-%d")
+                    .map_err(|_| AppError::BadRequest(format!("Invalid {} date", field)))
+            })
+            .transpose()
+    };
+    let created_from = parse_date(params.created_from.as_deref(), "created_from")?;
+    let created_to = parse_date(params.created_to.as_deref(), "created_to")?;
+    if let (Some(from), Some(to)) = (created_from, created_to) {
+        if from > to {
+            return Err(AppError::BadRequest("created_from must be <= created_to".to_string()));
+        }
+    }
+
+    let cursor = params
+        .cursor
+        .as_deref()
+        .map(|raw| {
+            raw.split_once('_')
+                .and_then(|(value, id)| Some(LinkCursor {
+                    sort_value: value.parse().ok()?,
+                    id: id.parse().ok()?,
+                }))
+                .ok_or_else(|| AppError::BadRequest("Invalid cursor".to_string()))
+        })
+        .transpose()?;
+
+    Ok(LinkSearch {
+        query: params.q.map(|q| q.trim().to_string()).filter(|q| !q.is_empty()),
+        status,
+        created_from,
+        created_to,
+        tag_id: params.tag_id,
+        sort,
+        descending,
+        cursor,
+        limit,
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/links/trash",
+    tag = "Links",
+    security(("bearer_auth" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "List my soft-deleted links", body = [LinkResponse]),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn get_trash(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
 ) -> AppResult<Json<Vec<LinkResponse>>> {
     let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
 
-    let links = link_service::get_user_links(&state.db, user_id).await
+    let links = link_service::get_user_trash(&state.db, user_id).await
         .map_err(AppError::Database)?;
 
-    let response = links.into_iter().map(|link| LinkResponse {
-        id: link.id,
-        short_code: link.short_code,
-        original_url: link.original_url,
-        title: link.title,
-        click_count: link.click_count.unwrap_or(0),
-    }).collect();
+    Ok(Json(links.into_iter().map(LinkResponse::from).collect()))
+}
 
-    Ok(Json(response))
+#[utoipa::path(
+    post,
+    path = "/links/{id}/restore",
+    tag = "Links",
+    security(("bearer_auth" = []), ("api_key" = [])),
+    params(("id" = i64, Path, description = "Link ID")),
+    responses(
+        (status = 200, description = "Restored link", body = LinkResponse),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse),
+        (status = 404, description = "Link not found in trash, or disabled by an admin", body = crate::error::ErrorResponse),
+        (status = 409, description = "Another active link already uses this URL", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn restore_link(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(link_id): Path<i64>,
+) -> AppResult<Json<LinkResponse>> {
+    let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
+
+    match link_service::restore_link(&state.db, user_id, link_id).await {
+        Ok(Some(link)) => Ok(Json(LinkResponse::from(link))),
+        Ok(None) => Err(AppError::NotFound(format!("Link {} not found in trash", link_id))),
+        Err(sqlx::Error::Io(io_err)) if io_err.to_string() == "LINK_EXISTS" => Err(AppError::Conflict(
+            "An active link for this URL already exists; delete it before restoring this one".to_string(),
+        )),
+        Err(e) => Err(AppError::Database(e)),
+    }
 }
 
 #[derive(serde::Deserialize, ToSchema)]
 pub struct AnalyticsQuery {
     pub from: String,
     pub to: String,
+    pub granularity: Option<String>,
+    pub tag_id: Option<i64>,
+    pub tz: Option<String>,
+    pub include_bots: Option<bool>,
+}
+
+#[utoipa::path(
+    patch,
+    path = "/links/{id}",
+    tag = "Links",
+    security(("bearer_auth" = []), ("api_key" = [])),
+    params(("id" = i64, Path, description = "Link ID")),
+    request_body = UpdateLinkRequest,
+    responses(
+        (status = 200, description = "Updated link", body = LinkResponse),
+        (status = 400, description = "Invalid input", body = crate::error::ErrorResponse),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse),
+        (status = 403, description = "Re-activating a link an admin disabled", body = crate::error::ErrorResponse),
+        (status = 404, description = "Link not found", body = crate::error::ErrorResponse),
+        (status = 409, description = "Another active link already uses this URL", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn update_link(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(link_id): Path<i64>,
+    Json(payload): Json<UpdateLinkRequest>,
+) -> AppResult<Json<LinkResponse>> {
+    let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
+
+    if let Some(original_url) = payload.original_url.as_deref() {
+        if !validate_url(original_url) {
+            return Err(AppError::BadRequest("Invalid URL (must be http/https)".to_string()));
+        }
+    }
+    if let Some(Some(title)) = payload.title.as_ref() {
+        if !validate_title(title) {
+            return Err(AppError::BadRequest("Title must be 1-255 characters".to_string()));
+        }
+    }
+    validate_redirect_policy(payload.redirect_type, payload.cache_max_age.flatten()).map_err(AppError::BadRequest)?;
+
+    let changes = LinkChanges {
+        original_url: payload.original_url,
+        title: payload.title,
+        is_active: payload.is_active,
+        redirect_type: payload.redirect_type,
+        cache_max_age: payload.cache_max_age,
+    };
+
+    match link_service::update_link(&state.db, user_id, link_id, &changes).await {
+        Ok(Some(link)) => {
+            if let Err(e) = cache_service::invalidate_cache(&state.redis, &link.short_code).await {
+                tracing::warn!("Redis cache invalidate error: {:?}", e);
+            }
+            Ok(Json(LinkResponse::from(link)))
+        }
+        Ok(None) => Err(AppError::NotFound(format!("Link {} not found", link_id))),
+        Err(sqlx::Error::Io(io_err)) if io_err.to_string() == "LINK_DISABLED_BY_ADMIN" => Err(AppError::Forbidden(
+            "This link was disabled by an admin and cannot be re-activated".to_string(),
+        )),
+        Err(sqlx::Error::Io(io_err)) if io_err.to_string() == "LINK_EXISTS" => Err(AppError::Conflict(
+            "You already have an active link for this URL".to_string(),
+        )),
+        Err(e) => Err(AppError::Database(e)),
+    }
 }
 
 #[utoipa::path(
     delete,
     path = "/links/{id}",
     tag = "Links",
-    security(("bearer_auth" = [])),
+    security(("bearer_auth" = []), ("api_key" = [])),
     params(("id" = i64, Path, description = "Link ID")),
     responses(
         (status = 200, description = "Soft delete success", body = DeleteLinkResponse),
@@ -161,14 +720,18 @@ pub async fn delete_link(
     get,
     path = "/links/analytics",
     tag = "Links",
-    security(("bearer_auth" = [])),
+    security(("bearer_auth" = []), ("api_key" = [])),
     params(
         ("from" = String, Query, description = "Start date (YYYY-MM-DD)"),
-        ("to" = String, Query, description = "End date (YYYY-MM-DD)")
+        ("to" = String, Query, description = "End date (YYYY-MM-DD)"),
+        ("granularity" = Option<String>, Query, description = "hour (ranges up to 31 days), day (default, up to 731 days), week or month (up to 3653 days)"),
+        ("tag_id" = Option<i64>, Query, description = "Only count links carrying this tag"),
+        ("tz" = Option<String>, Query, description = "IANA timezone for day boundaries (default: the user's timezone)"),
+        ("include_bots" = Option<bool>, Query, description = "Also count crawler, link preview and prefetch hits (default false)")
     ),
     responses(
-        (status = 200, description = "Daily analytics", body = [DailyAnalyticsResponse]),
-        (status = 400, description = "Invalid date range", body = crate::error::ErrorResponse),
+        (status = 200, description = "Click series, one zero-filled entry per bucket", body = [DailyAnalyticsResponse]),
+        (status = 400, description = "Invalid date range, granularity or timezone", body = crate::error::ErrorResponse),
         (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse)
     )
 )]
@@ -179,26 +742,324 @@ pub async fn get_daily_analytics(
 ) -> AppResult<Json<Vec<DailyAnalyticsResponse>>> {
     let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
 
-    let from_date = NaiveDate::parse_from_str(&params.from, "%Y-%m-%d")
-        .map_err(|_| AppError::BadRequest("Invalid from date".to_string()))?;
-    let to_date = NaiveDate::parse_from_str(&params.to, "%Y-%m-%d")
-        .map_err(|_| AppError::BadRequest("Invalid to date".to_string()))?;
+    let (from_date, to_date) = parse_date_range(&params.from, &params.to)?;
+    let granularity = parse_granularity(params.granularity.as_deref(), from_date, to_date)?;
+    let timezone = analytics_timezone(&state, user_id, params.tz.as_deref()).await?;
 
-    if from_date > to_date {
-        return Err(AppError::BadRequest("from must be <= to".to_string()));
-    }
+    let filter = ClickEventFilter {
+        owner_id: user_id,
+        link_id: None,
+        tag_id: params.tag_id,
+        from_date,
+        to_date,
+        timezone,
+        include_bots: params.include_bots.unwrap_or(false),
+    };
 
-    let totals = link_service::get_daily_analytics(&state.db, user_id, from_date, to_date)
+    let buckets = link_service::get_analytics_series(&state.db, &state.redis, &filter, granularity)
         .await
         .map_err(AppError::Database)?;
 
-    let response = totals
+    let response = buckets
         .into_iter()
         .map(|item| DailyAnalyticsResponse {
-            date: item.date.format("%Y-%m-%d").to_string(),
+            date: item.period.format("%Y-%m-%d").to_string(),
+            period: format_period(item.period, granularity),
             total_clicks: item.total_clicks,
+            uniques: item.uniques,
         })
         .collect();
 
     Ok(Json(response))
 }
+
+#[derive(serde::Deserialize, ToSchema)]
+pub struct LinkAnalyticsQuery {
+    pub from: String,
+    pub to: String,
+    pub granularity: Option<String>,
+    pub tz: Option<String>,
+    pub include_bots: Option<bool>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/links/{id}/analytics",
+    tag = "Links",
+    security(("bearer_auth" = []), ("api_key" = [])),
+    params(
+        ("id" = i64, Path, description = "Link ID"),
+        ("from" = String, Query, description = "Start date (YYYY-MM-DD)"),
+        ("to" = String, Query, description = "End date (YYYY-MM-DD)"),
+        ("granularity" = Option<String>, Query, description = "hour (ranges up to 31 days), day (default, up to 731 days), week or month (up to 3653 days)"),
+        ("tz" = Option<String>, Query, description = "IANA timezone for day boundaries (default: the user's timezone)"),
+        ("include_bots" = Option<bool>, Query, description = "Also count crawler, link preview and prefetch hits (default false)")
+    ),
+    responses(
+        (status = 200, description = "Analytics for one link", body = LinkAnalyticsResponse),
+        (status = 400, description = "Invalid date range, granularity or timezone", body = crate::error::ErrorResponse),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse),
+        (status = 404, description = "Link not found", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn get_link_analytics(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(link_id): Path<i64>,
+    Query(params): Query<LinkAnalyticsQuery>,
+) -> AppResult<Json<LinkAnalyticsResponse>> {
+    let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
+
+    let (from_date, to_date) = parse_date_range(&params.from, &params.to)?;
+    let granularity = parse_granularity(params.granularity.as_deref(), from_date, to_date)?;
+    let timezone = analytics_timezone(&state, user_id, params.tz.as_deref()).await?;
+
+    let filter = ClickEventFilter {
+        owner_id: user_id,
+        link_id: Some(link_id),
+        tag_id: None,
+        from_date,
+        to_date,
+        timezone,
+        include_bots: params.include_bots.unwrap_or(false),
+    };
+
+    let report = link_service::get_link_analytics(&state.db, &filter, granularity)
+        .await
+        .map_err(AppError::Database)?
+        .ok_or_else(|| AppError::NotFound(format!("Link {} not found", link_id)))?;
+
+    let change_percent = (report.previous_total_clicks > 0).then(|| {
+        let change = (report.total_clicks - report.previous_total_clicks) as f64 / report.previous_total_clicks as f64;
+        (change * 10_000.0).round() / 100.0
+    });
+
+    Ok(Json(LinkAnalyticsResponse {
+        link_id: report.link.id,
+        short_code: report.link.short_code,
+        from: from_date.format("%Y-%m-%d").to_string(),
+        to: to_date.format("%Y-%m-%d").to_string(),
+        granularity: granularity.as_unit().to_string(),
+        timezone: filter.timezone,
+        series: report
+            .series
+            .into_iter()
+            .map(|point| ClickSeriesPointResponse {
+                period: format_period(point.period, granularity),
+                clicks: point.clicks,
+            })
+            .collect(),
+        total_clicks: report.total_clicks,
+        lifetime_clicks: report.link.click_count.unwrap_or(0),
+        first_click_at: report.first_click_at,
+        last_click_at: report.last_click_at,
+        previous_period: PeriodComparisonResponse {
+            from: report.previous_from.format("%Y-%m-%d").to_string(),
+            to: report.previous_to.format("%Y-%m-%d").to_string(),
+            total_clicks: report.previous_total_clicks,
+            change_percent,
+        },
+    }))
+}
+
+const BREAKDOWN_DEFAULT_LIMIT: usize = 10;
+const BREAKDOWN_MAX_LIMIT: usize = 50;
+
+#[derive(serde::Deserialize, ToSchema)]
+pub struct BreakdownQuery {
+    pub from: String,
+    pub to: String,
+    pub tz: Option<String>,
+    pub limit: Option<usize>,
+    /// Split browsers by major version ("Chrome 129").
+    pub versions: Option<bool>,
+    pub tag_id: Option<i64>,
+    pub include_bots: Option<bool>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/links/analytics/{dimension}",
+    tag = "Links",
+    security(("bearer_auth" = []), ("api_key" = [])),
+    params(
+        ("dimension" = String, Path, description = "referrers, devices, browsers or os"),
+        ("from" = String, Query, description = "Start date (YYYY-MM-DD)"),
+        ("to" = String, Query, description = "End date (YYYY-MM-DD)"),
+        ("tz" = Option<String>, Query, description = "IANA timezone for day boundaries (default: the user's timezone)"),
+        ("limit" = Option<usize>, Query, description = "Number of top entries before the \"Other\" bucket (default 10, max 50)"),
+        ("versions" = Option<bool>, Query, description = "Split browsers by major version"),
+        ("tag_id" = Option<i64>, Query, description = "Only count links carrying this tag"),
+        ("include_bots" = Option<bool>, Query, description = "Also count crawler, link preview and prefetch hits (default false)")
+    ),
+    responses(
+        (status = 200, description = "Clicks on all of the user's links, grouped by dimension", body = BreakdownResponse),
+        (status = 400, description = "Invalid dimension, date range, limit or timezone", body = crate::error::ErrorResponse),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn get_breakdown(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(dimension): Path<String>,
+    Query(params): Query<BreakdownQuery>,
+) -> AppResult<Json<BreakdownResponse>> {
+    let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
+    click_breakdown(&state, user_id, None, &dimension, params).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/links/{id}/analytics/{dimension}",
+    tag = "Links",
+    security(("bearer_auth" = []), ("api_key" = [])),
+    params(
+        ("id" = i64, Path, description = "Link ID"),
+        ("dimension" = String, Path, description = "referrers, devices, browsers or os"),
+        ("from" = String, Query, description = "Start date (YYYY-MM-DD)"),
+        ("to" = String, Query, description = "End date (YYYY-MM-DD)"),
+        ("tz" = Option<String>, Query, description = "IANA timezone for day boundaries (default: the user's timezone)"),
+        ("limit" = Option<usize>, Query, description = "Number of top entries before the \"Other\" bucket (default 10, max 50)"),
+        ("versions" = Option<bool>, Query, description = "Split browsers by major version"),
+        ("include_bots" = Option<bool>, Query, description = "Also count crawler, link preview and prefetch hits (default false)")
+    ),
+    responses(
+        (status = 200, description = "Clicks on one link, grouped by dimension", body = BreakdownResponse),
+        (status = 400, description = "Invalid dimension, date range, limit or timezone", body = crate::error::ErrorResponse),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse),
+        (status = 404, description = "Link not found", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn get_link_breakdown(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path((link_id, dimension)): Path<(i64, String)>,
+    Query(params): Query<BreakdownQuery>,
+) -> AppResult<Json<BreakdownResponse>> {
+    let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
+    click_breakdown(&state, user_id, Some(link_id), &dimension, params).await
+}
+
+async fn click_breakdown(
+    state: &AppState,
+    user_id: i64,
+    link_id: Option<i64>,
+    dimension: &str,
+    params: BreakdownQuery,
+) -> AppResult<Json<BreakdownResponse>> {
+    let dimension = match dimension {
+        "referrers" => BreakdownDimension::Referrers,
+        "devices" => BreakdownDimension::Devices,
+        "browsers" => BreakdownDimension::Browsers,
+        "os" => BreakdownDimension::Os,
+        _ => return Err(AppError::BadRequest("dimension must be referrers, devices, browsers or os".to_string())),
+    };
+    let (from_date, to_date) = parse_date_range(&params.from, &params.to)?;
+    let limit = params.limit.unwrap_or(BREAKDOWN_DEFAULT_LIMIT);
+    if limit == 0 || limit > BREAKDOWN_MAX_LIMIT {
+        return Err(AppError::BadRequest(format!("limit must be between 1 and {}", BREAKDOWN_MAX_LIMIT)));
+    }
+    let timezone = analytics_timezone(state, user_id, params.tz.as_deref()).await?;
+
+    let filter = ClickEventFilter {
+        owner_id: user_id,
+        link_id,
+        tag_id: if link_id.is_some() { None } else { params.tag_id },
+        from_date,
+        to_date,
+        timezone,
+        include_bots: params.include_bots.unwrap_or(false),
+    };
+
+    let items = link_service::get_click_breakdown(&state.db, &filter, dimension, limit, params.versions.unwrap_or(false))
+        .await
+        .map_err(AppError::Database)?
+        .ok_or_else(|| AppError::NotFound(format!("Link {} not found", link_id.unwrap_or_default())))?;
+
+    let total_clicks: i64 = items.iter().map(|item| item.clicks).sum();
+    let items = items
+        .into_iter()
+        .map(|item| BreakdownItemResponse {
+            share_percent: if total_clicks > 0 {
+                (item.clicks as f64 * 10_000.0 / total_clicks as f64).round() / 100.0
+            } else {
+                0.0
+            },
+            label: item.label,
+            clicks: item.clicks,
+        })
+        .collect();
+
+    Ok(Json(BreakdownResponse {
+        dimension: dimension.as_str().to_string(),
+        link_id,
+        from: from_date.format("%Y-%m-%d").to_string(),
+        to: to_date.format("%Y-%m-%d").to_string(),
+        timezone: filter.timezone,
+        total_clicks,
+        items,
+    }))
+}
+
+/// Timezone for an analytics request: `tz` when given and valid, else the user's default.
+pub async fn analytics_timezone(state: &AppState, user_id: i64, tz: Option<&str>) -> AppResult<String> {
+    match user_service::resolve_timezone(&state.db, user_id, tz.map(str::trim)).await {
+        Ok(timezone) => Ok(timezone),
+        Err(sqlx::Error::Io(io_err)) if io_err.to_string() == "TIMEZONE_INVALID" => {
+            Err(AppError::BadRequest("tz must be an IANA timezone such as Asia/Ho_Chi_Minh".to_string()))
+        }
+        Err(sqlx::Error::RowNotFound) => Err(AppError::NotFound(format!("User {} not found", user_id))),
+        Err(e) => Err(AppError::Database(e)),
+    }
+}
+
+/// Longest range, in days, that may be requested per granularity. Every bucket
+/// of the range is returned, even empty ones, so the range bounds the response.
+const MAX_HOURLY_RANGE_DAYS: i64 = 31;
+const MAX_DAILY_RANGE_DAYS: i64 = 731;
+const MAX_WEEKLY_MONTHLY_RANGE_DAYS: i64 = 3653;
+
+pub fn parse_granularity(value: Option<&str>, from_date: NaiveDate, to_date: NaiveDate) -> AppResult<AnalyticsGranularity> {
+    let granularity = match value {
+        Some("hour") => AnalyticsGranularity::Hour,
+        None | Some("day") => AnalyticsGranularity::Day,
+        Some("week") => AnalyticsGranularity::Week,
+        Some("month") => AnalyticsGranularity::Month,
+        Some(_) => return Err(AppError::BadRequest("granularity must be hour, day, week or month".to_string())),
+    };
+
+    let max_days = match granularity {
+        AnalyticsGranularity::Hour => MAX_HOURLY_RANGE_DAYS,
+        AnalyticsGranularity::Day => MAX_DAILY_RANGE_DAYS,
+        AnalyticsGranularity::Week | AnalyticsGranularity::Month => MAX_WEEKLY_MONTHLY_RANGE_DAYS,
+    };
+    if (to_date - from_date).num_days() >= max_days {
+        return Err(AppError::BadRequest(format!(
+            "Analytics by {} cover at most {} days",
+            granularity.as_unit(),
+            max_days
+        )));
+    }
+
+    Ok(granularity)
+}
+
+pub fn format_period(period: NaiveDateTime, granularity: AnalyticsGranularity) -> String {
+    match granularity {
+        AnalyticsGranularity::Hour => period.format("%Y-%m-%dT%H:00").to_string(),
+        _ => period.format("%Y-%m-%d").to_string(),
+    }
+}
+
+pub fn parse_date_range(from: &str, to: &str) -> AppResult<(NaiveDate, NaiveDate)> {
+    let from_date = NaiveDate::parse_from_str(from, "%Y-%m-%d")
+        .map_err(|_| AppError::BadRequest("Invalid from date".to_string()))?;
+    let to_date = NaiveDate::parse_from_str(to, "%Y-%m-%d")
+        .map_err(|_| AppError::BadRequest("Invalid to date".to_string()))?;
+
+    if from_date > to_date {
+        return Err(AppError::BadRequest("from must be <= to".to_string()));
+    }
+
+    Ok((from_date, to_date))
+}
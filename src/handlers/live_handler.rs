@@ -0,0 +1,100 @@
+use std::convert::Infallible;
+use std::time::Duration;
+
+use axum::{
+    extract::{Path, Query, State},
+    response::sse::{Event, KeepAlive, Sse},
+    Extension,
+};
+use futures_util::{Stream, StreamExt};
+use utoipa::ToSchema;
+
+use crate::dtos::claims::Claims;
+use crate::error::{AppError, AppResult};
+use crate::services::link_service;
+use crate::state::AppState;
+
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(serde::Deserialize, ToSchema)]
+pub struct LiveClicksQuery {
+    pub include_bots: Option<bool>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/links/live",
+    tag = "Links",
+    security(("bearer_auth" = []), ("api_key" = [])),
+    params(("include_bots" = Option<bool>, Query, description = "Also stream crawler, link preview and prefetch hits (default false)")),
+    responses(
+        (status = 200, description = "Server-Sent Events: one `click` event per click on any of the user's links, data = {link_id, clicked_at, referrer_host, device, is_bot}", content_type = "text/event-stream", body = String),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn live_clicks(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(params): Query<LiveClicksQuery>,
+) -> AppResult<Sse<impl Stream<Item = Result<Event, Infallible>>>> {
+    let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
+    Ok(live_stream(&state, user_id, None, params.include_bots.unwrap_or(false)).await)
+}
+
+#[utoipa::path(
+    get,
+    path = "/links/{id}/live",
+    tag = "Links",
+    security(("bearer_auth" = []), ("api_key" = [])),
+    params(
+        ("id" = i64, Path, description = "Link ID"),
+        ("include_bots" = Option<bool>, Query, description = "Also stream crawler, link preview and prefetch hits (default false)")
+    ),
+    responses(
+        (status = 200, description = "Server-Sent Events: one `click` event per click on this link, data = {link_id, clicked_at, referrer_host, device, is_bot}", content_type = "text/event-stream", body = String),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse),
+        (status = 404, description = "Link not found", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn live_link_clicks(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(link_id): Path<i64>,
+    Query(params): Query<LiveClicksQuery>,
+) -> AppResult<Sse<impl Stream<Item = Result<Event, Infallible>>>> {
+    let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
+
+    link_service::get_user_link(&state.db, user_id, link_id)
+        .await
+        .map_err(AppError::Database)?
+        .ok_or_else(|| AppError::NotFound(format!("Link {} not found", link_id)))?;
+
+    Ok(live_stream(&state, user_id, Some(link_id), params.include_bots.unwrap_or(false)).await)
+}
+
+async fn live_stream(
+    state: &AppState,
+    user_id: i64,
+    link_id: Option<i64>,
+    include_bots: bool,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let events = state
+        .live_clicks
+        .subscribe(user_id)
+        .await
+        .filter(move |click| {
+            let wanted = link_id.is_none_or(|id| id == click.link_id) && (include_bots || !click.is_bot);
+            std::future::ready(wanted)
+        })
+        .filter_map(|click| async move {
+            match Event::default().event("click").json_data(&click) {
+                Ok(event) => Some(Ok(event)),
+                Err(e) => {
+                    tracing::warn!("Live click encode failed: {:?}", e);
+                    None
+                }
+            }
+        });
+
+    Sse::new(events).keep_alive(KeepAlive::new().interval(KEEP_ALIVE_INTERVAL))
+}
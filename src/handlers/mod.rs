@@ -2,3 +2,8 @@ pub mod link_handler;
 pub mod user_handler;
 pub mod health_handler;
 pub mod admin_handler;
+pub mod tag_handler;
+pub mod export_handler;
+pub mod live_handler;
+pub mod alert_handler;
+pub mod api_key_handler;
@@ -0,0 +1,279 @@
+use axum::{
+    Json,
+    extract::{Path, State, Query},
+    Extension,
+};
+use crate::error::{AppError, AppResult};
+use crate::services::tag_service;
+use crate::dtos::tag::{CreateTagRequest, TagAnalyticsResponse, TagMessageResponse, TagResponse, UpdateTagRequest};
+use crate::dtos::claims::Claims;
+use crate::handlers::link_handler::{analytics_timezone, parse_date_range, AnalyticsQuery};
+use crate::state::AppState;
+use crate::utils::validation::{validate_tag_color, validate_tag_name};
+
+#[utoipa::path(
+    get,
+    path = "/tags",
+    tag = "Tags",
+    security(("bearer_auth" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "List my tags", body = [TagResponse]),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn list_tags(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> AppResult<Json<Vec<TagResponse>>> {
+    let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
+
+    let tags = tag_service::list_tags(&state.db, user_id).await
+        .map_err(AppError::Database)?;
+
+    Ok(Json(tags.into_iter().map(TagResponse::from).collect()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/tags",
+    tag = "Tags",
+    security(("bearer_auth" = []), ("api_key" = [])),
+    request_body = CreateTagRequest,
+    responses(
+        (status = 200, description = "Create tag", body = TagResponse),
+        (status = 400, description = "Invalid input", body = crate::error::ErrorResponse),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse),
+        (status = 409, description = "Tag name already used", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn create_tag(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<CreateTagRequest>,
+) -> AppResult<Json<TagResponse>> {
+    let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
+
+    validate_tag_input(Some(&payload.name), payload.color.as_deref())?;
+
+    match tag_service::create_tag(&state.db, user_id, &payload.name, payload.color.as_deref()).await {
+        Ok(tag) => Ok(Json(TagResponse::from(tag))),
+        Err(sqlx::Error::Io(io_err)) if io_err.to_string() == "TAG_EXISTS" => {
+            Err(AppError::Conflict("You already have a tag with this name".to_string()))
+        }
+        Err(e) => Err(AppError::Database(e)),
+    }
+}
+
+#[utoipa::path(
+    patch,
+    path = "/tags/{id}",
+    tag = "Tags",
+    security(("bearer_auth" = []), ("api_key" = [])),
+    params(("id" = i64, Path, description = "Tag ID")),
+    request_body = UpdateTagRequest,
+    responses(
+        (status = 200, description = "Updated tag", body = TagResponse),
+        (status = 400, description = "Invalid input", body = crate::error::ErrorResponse),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse),
+        (status = 404, description = "Tag not found", body = crate::error::ErrorResponse),
+        (status = 409, description = "Tag name already used", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn update_tag(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(tag_id): Path<i64>,
+    Json(payload): Json<UpdateTagRequest>,
+) -> AppResult<Json<TagResponse>> {
+    let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
+
+    validate_tag_input(payload.name.as_deref(), payload.color.as_deref())?;
+
+    match tag_service::update_tag(&state.db, user_id, tag_id, payload.name.as_deref(), payload.color.as_deref()).await {
+        Ok(Some(tag)) => Ok(Json(TagResponse::from(tag))),
+        Ok(None) => Err(AppError::NotFound(format!("Tag {} not found", tag_id))),
+        Err(sqlx::Error::Io(io_err)) if io_err.to_string() == "TAG_EXISTS" => {
+            Err(AppError::Conflict("You already have a tag with this name".to_string()))
+        }
+        Err(e) => Err(AppError::Database(e)),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/tags/{id}",
+    tag = "Tags",
+    security(("bearer_auth" = []), ("api_key" = [])),
+    params(("id" = i64, Path, description = "Tag ID")),
+    responses(
+        (status = 200, description = "Tag deleted and removed from its links", body = TagMessageResponse),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse),
+        (status = 404, description = "Tag not found", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn delete_tag(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(tag_id): Path<i64>,
+) -> AppResult<Json<TagMessageResponse>> {
+    let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
+
+    match tag_service::delete_tag(&state.db, user_id, tag_id).await {
+        Ok(true) => Ok(Json(TagMessageResponse {
+            message: "Tag deleted".to_string(),
+        })),
+        Ok(false) => Err(AppError::NotFound(format!("Tag {} not found", tag_id))),
+        Err(e) => Err(AppError::Database(e)),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/links/{id}/tags",
+    tag = "Tags",
+    security(("bearer_auth" = []), ("api_key" = [])),
+    params(("id" = i64, Path, description = "Link ID")),
+    responses(
+        (status = 200, description = "Tags on the link", body = [TagResponse]),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn get_link_tags(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(link_id): Path<i64>,
+) -> AppResult<Json<Vec<TagResponse>>> {
+    let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
+
+    let tags = tag_service::get_link_tags(&state.db, user_id, link_id).await
+        .map_err(AppError::Database)?;
+
+    Ok(Json(tags.into_iter().map(TagResponse::from).collect()))
+}
+
+#[utoipa::path(
+    put,
+    path = "/links/{id}/tags/{tag_id}",
+    tag = "Tags",
+    security(("bearer_auth" = []), ("api_key" = [])),
+    params(
+        ("id" = i64, Path, description = "Link ID"),
+        ("tag_id" = i64, Path, description = "Tag ID")
+    ),
+    responses(
+        (status = 200, description = "Tag assigned", body = TagMessageResponse),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse),
+        (status = 404, description = "Link or tag not found", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn add_tag_to_link(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path((link_id, tag_id)): Path<(i64, i64)>,
+) -> AppResult<Json<TagMessageResponse>> {
+    let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
+
+    match tag_service::assign_tag(&state.db, user_id, link_id, tag_id).await {
+        Ok(true) => Ok(Json(TagMessageResponse {
+            message: "Tag assigned".to_string(),
+        })),
+        Ok(false) => Err(AppError::NotFound(format!("Link {} or tag {} not found", link_id, tag_id))),
+        Err(e) => Err(AppError::Database(e)),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/links/{id}/tags/{tag_id}",
+    tag = "Tags",
+    security(("bearer_auth" = []), ("api_key" = [])),
+    params(
+        ("id" = i64, Path, description = "Link ID"),
+        ("tag_id" = i64, Path, description = "Tag ID")
+    ),
+    responses(
+        (status = 200, description = "Tag removed from link", body = TagMessageResponse),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse),
+        (status = 404, description = "Tag is not on this link", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn remove_tag_from_link(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path((link_id, tag_id)): Path<(i64, i64)>,
+) -> AppResult<Json<TagMessageResponse>> {
+    let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
+
+    match tag_service::unassign_tag(&state.db, user_id, link_id, tag_id).await {
+        Ok(true) => Ok(Json(TagMessageResponse {
+            message: "Tag removed".to_string(),
+        })),
+        Ok(false) => Err(AppError::NotFound(format!("Tag {} is not on link {}", tag_id, link_id))),
+        Err(e) => Err(AppError::Database(e)),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/tags/analytics",
+    tag = "Tags",
+    security(("bearer_auth" = []), ("api_key" = [])),
+    params(
+        ("from" = String, Query, description = "Start date (YYYY-MM-DD)"),
+        ("to" = String, Query, description = "End date (YYYY-MM-DD)"),
+        ("tz" = Option<String>, Query, description = "IANA timezone for day boundaries (default: the user's timezone)"),
+        ("include_bots" = Option<bool>, Query, description = "Also count crawler, link preview and prefetch hits (default false)")
+    ),
+    responses(
+        (status = 200, description = "Click totals per tag", body = [TagAnalyticsResponse]),
+        (status = 400, description = "Invalid date range or timezone", body = crate::error::ErrorResponse),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn get_tag_analytics(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(params): Query<AnalyticsQuery>,
+) -> AppResult<Json<Vec<TagAnalyticsResponse>>> {
+    let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID".to_string()))?;
+
+    let (from_date, to_date) = parse_date_range(&params.from, &params.to)?;
+    let timezone = analytics_timezone(&state, user_id, params.tz.as_deref()).await?;
+
+    let totals = tag_service::get_tag_analytics(
+        &state.db,
+        user_id,
+        from_date,
+        to_date,
+        &timezone,
+        params.include_bots.unwrap_or(false),
+    )
+        .await
+        .map_err(AppError::Database)?;
+
+    let response = totals
+        .into_iter()
+        .map(|item| TagAnalyticsResponse {
+            tag_id: item.tag_id,
+            name: item.name,
+            link_count: item.link_count,
+            total_clicks: item.total_clicks,
+        })
+        .collect();
+
+    Ok(Json(response))
+}
+
+fn validate_tag_input(name: Option<&str>, color: Option<&str>) -> AppResult<()> {
+    if let Some(name) = name {
+        if !validate_tag_name(name) {
+            return Err(AppError::BadRequest("Tag name must be 1-50 characters".to_string()));
+        }
+    }
+    if let Some(color) = color {
+        if !validate_tag_color(color) {
+            return Err(AppError::BadRequest("Tag color must look like #1f8a5b".to_string()));
+        }
+    }
+    Ok(())
+}
@@ -6,7 +6,7 @@ use axum::{
 use crate::error::{AppError, AppResult};
 use crate::services::user_service;
 use crate::dtos::claims::Claims;
-use crate::dtos::user::{LoginResponse, LoginUser, LogoutRequest, LogoutResponse, RefreshTokenRequest, RefreshTokenResponse, RegisterUser, UserResponse};
+use crate::dtos::user::{LoginResponse, LoginUser, LogoutRequest, LogoutResponse, RefreshTokenRequest, RefreshTokenResponse, RegisterUser, UpdateUserRequest, UserResponse};
 use crate::state::AppState;
 use crate::utils::validation::{validate_email, validate_password, validate_username};
 
@@ -37,6 +37,40 @@ pub async fn get_me(
     }
 }
 
+#[utoipa::path(
+    patch,
+    path = "/users/me",
+    tag = "Users",
+    security(("bearer_auth" = [])),
+    request_body = UpdateUserRequest,
+    responses(
+        (status = 200, description = "Updated user", body = UserResponse),
+        (status = 400, description = "Unknown timezone", body = crate::error::ErrorResponse),
+        (status = 401, description = "Unauthorized", body = crate::error::ErrorResponse),
+        (status = 404, description = "User not found", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn update_me(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<UpdateUserRequest>,
+) -> AppResult<Json<UserResponse>> {
+    let user_id = claims.sub.parse::<i64>().map_err(|_| AppError::Unauthorized("Invalid user ID in token".to_string()))?;
+
+    let Some(timezone) = payload.timezone.as_deref().map(str::trim) else {
+        return get_me(State(state), Extension(claims)).await;
+    };
+
+    match user_service::update_timezone(&state.db, user_id, timezone).await {
+        Ok(Some(user)) => Ok(Json(UserResponse::from(user))),
+        Ok(None) => Err(AppError::NotFound(format!("User {} not found", user_id))),
+        Err(sqlx::Error::Io(io_err)) if io_err.to_string() == "TIMEZONE_INVALID" => {
+            Err(AppError::BadRequest(format!("Unknown timezone: {}", timezone)))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
 #[utoipa::path(
     post,
     path = "/register",
@@ -114,8 +148,9 @@ pub async fn login_user(
     tag = "Auth",
     request_body = RefreshTokenRequest,
     responses(
-        (status = 200, description = "Refresh success", body = RefreshTokenResponse),
-        (status = 401, description = "Invalid refresh token", body = crate::error::ErrorResponse)
+        (status = 200, description = "Refresh success; the sent refresh token is revoked and replaced", body = RefreshTokenResponse),
+        (status = 401, description = "Invalid refresh token, or one already used (which revokes the whole login session)", body = crate::error::ErrorResponse),
+        (status = 403, description = "User disabled", body = crate::error::ErrorResponse)
     )
 )]
 pub async fn refresh_token(
@@ -127,6 +162,9 @@ pub async fn refresh_token(
         Err(sqlx::Error::Io(io_err)) if io_err.to_string() == "REFRESH_TOKEN_INVALID" => {
             Err(AppError::Unauthorized("Refresh token khong hop le".to_string()))
         }
+        Err(sqlx::Error::Io(io_err)) if io_err.to_string() == "REFRESH_TOKEN_REUSED" => {
+            Err(AppError::Unauthorized("Refresh token da duoc su dung, vui long dang nhap lai".to_string()))
+        }
         Err(sqlx::Error::Io(io_err)) if io_err.to_string() == "USER_DISABLED" => {
             Err(AppError::Forbidden("Tai khoan da bi vo hieu hoa".to_string()))
         }
@@ -1,6 +1,7 @@
 use axum::{Json, Router, serve};
 use dotenvy::dotenv;
 use std::env;
+use std::net::SocketAddr;
 use std::time::Duration;
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
@@ -8,7 +9,12 @@ use utoipa::OpenApi;
 
 use crate::db::init_db;
 use crate::middleware::rate_limit_middleware::{RateLimiter, rate_limit_middleware};
-use crate::routes::{admin_route, health_route, link_route, user_route};
+use crate::services::{alert_service, click_service};
+use crate::services::click_event_service::ClickEventWriter;
+use crate::services::link_service::CodeStrategy;
+use crate::services::live_click_service::LiveClickHub;
+use crate::services::retention_service::{self, RetentionPolicy};
+use crate::routes::{admin_route, alert_route, api_key_route, health_route, link_route, tag_route, user_route};
 use crate::state::AppState;
 use deadpool_redis::{Config as RedisConfig, Runtime, PoolConfig};
 use axum::http::{HeaderValue, Method};
@@ -32,6 +38,14 @@ async fn main() {
     dotenv().ok();
     tracing_subscriber::fmt::init();
 
+    let code_strategy = match CodeStrategy::from_env() {
+        Ok(strategy) => strategy,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+
     let database_url = match env::var("DATABASE_URL") {
         Ok(url) => url,
         Err(e) => {
@@ -49,6 +63,13 @@ async fn main() {
         .ok()
         .and_then(|v| v.parse::<usize>().ok())
         .unwrap_or(32);
+    let live_clicks = match LiveClickHub::new(&redis_url) {
+        Ok(hub) => hub,
+        Err(e) => {
+            eprintln!("Invalid REDIS_URL: {}", e);
+            return;
+        }
+    };
     let mut redis_cfg = RedisConfig::from_url(redis_url);
     redis_cfg.pool = Some(PoolConfig::new(redis_max));
 
@@ -60,7 +81,28 @@ async fn main() {
         }
     };
 
-    let state = AppState::new(db_pool, redis_pool);
+    let click_flusher = click_service::spawn_click_flusher(
+        db_pool.clone(),
+        redis_pool.clone(),
+        click_service::flush_interval_from_env(),
+    );
+
+    let (click_events, click_event_writer) = ClickEventWriter::spawn(db_pool.clone());
+
+    let retention = RetentionPolicy::from_env();
+    let retention_job = retention.map(|policy| {
+        retention_service::spawn_retention_job(
+            db_pool.clone(),
+            policy,
+            retention_service::retention_interval_from_env(),
+        )
+    });
+
+    let live_listener = live_clicks.spawn_listener();
+
+    let alert_evaluator = alert_service::spawn_alert_evaluator(db_pool.clone(), alert_service::alert_interval_from_env());
+
+    let state = AppState::new(db_pool.clone(), redis_pool.clone(), click_events, live_clicks.clone(), retention, code_strategy);
     let rate_limiter = RateLimiter::from_env();
 
     let allowed_origins = env::var("CORS_ALLOWED_ORIGINS")
@@ -69,7 +111,7 @@ async fn main() {
     let cors_layer = if allowed_origins.trim() == "*" {
         CorsLayer::new()
             .allow_origin(Any)
-            .allow_methods([Method::GET, Method::POST, Method::DELETE, Method::OPTIONS])
+            .allow_methods([Method::GET, Method::POST, Method::PUT, Method::PATCH, Method::DELETE, Method::OPTIONS])
             .allow_headers(Any)
             .max_age(Duration::from_secs(3600))
     } else {
@@ -80,7 +122,7 @@ async fn main() {
 
         CorsLayer::new()
             .allow_origin(origins)
-            .allow_methods([Method::GET, Method::POST, Method::DELETE, Method::OPTIONS])
+            .allow_methods([Method::GET, Method::POST, Method::PUT, Method::PATCH, Method::DELETE, Method::OPTIONS])
             .allow_headers(Any)
             .max_age(Duration::from_secs(3600))
     };
@@ -94,8 +136,11 @@ async fn main() {
             axum::routing::get(|| async { Json(docs::ApiDoc::openapi()) }),
         )
         .merge(health_route::routes())
-        .merge(user_route::routes())
-        .merge(link_route::routes())
+        .merge(user_route::routes(state.clone()))
+        .merge(link_route::routes(state.clone()))
+        .merge(tag_route::routes(state.clone()))
+        .merge(alert_route::routes(state.clone()))
+        .merge(api_key_route::routes(state.clone()))
         .merge(admin_route::routes())
         .layer(axum_middleware::from_fn_with_state(rate_limiter, rate_limit_middleware))
         .layer(cors_layer)
@@ -113,7 +158,64 @@ async fn main() {
     };
     println!("Server started at http://{}", bind_addr);
 
-    if let Err(e) = serve(listener, app).await {
+    let service = app.into_make_service_with_connect_info::<SocketAddr>();
+    // Live streams never end on their own, so close them once the signal arrives
+    // or graceful shutdown would wait on them forever.
+    let shutdown = async move {
+        shutdown_signal().await;
+        live_clicks.close();
+    };
+    if let Err(e) = serve(listener, service).with_graceful_shutdown(shutdown).await {
         eprintln!("Server error: {}", e);
     }
+
+    // The router (and with it every writer handle) is gone, so the event writer
+    // drains its queue and stops.
+    if let Err(e) = click_event_writer.await {
+        eprintln!("Click event writer failed: {}", e);
+    }
+
+    alert_evaluator.abort();
+    live_listener.abort();
+    if let Some(job) = retention_job {
+        job.abort();
+    }
+
+    // Stop the interval flusher and push whatever is still buffered before exiting.
+    // A flush interrupted by the abort is picked up again by this final one.
+    click_flusher.abort();
+    match click_service::flush_pending_clicks(&db_pool, &redis_pool).await {
+        Ok(clicks) => println!("Flushed {} buffered clicks", clicks),
+        Err(e) => eprintln!("Final click flush failed: {}", e),
+    }
+}
+
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            eprintln!("Failed to listen for Ctrl+C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                eprintln!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
 }
@@ -1,32 +1,81 @@
 use crate::error::AppError;
+use crate::models::api_key::ApiScope;
+use crate::services::api_key_service::{self, API_KEY_PREFIX};
+use crate::state::AppState;
 use crate::utils::jwt::decode_jwt;
 use axum::{
-    extract::Request,
-    http::header,
+    extract::{MatchedPath, Request, State},
+    http::{header, Method},
     middleware::Next,
     response::Response,
 };
 
+const API_KEY_HEADER: &str = "x-api-key";
+
+/// Accepts a login JWT as `Authorization: Bearer <jwt>`, or an API key either
+/// in `X-API-Key` or as the Bearer token. API keys only reach routes their
+/// scopes cover.
 pub async fn auth_middleware(
+    State(state): State<AppState>,
     mut req: Request,
     next: Next,
 ) -> Result<Response, AppError> {
+    let api_key_header = req
+        .headers()
+        .get(API_KEY_HEADER)
+        .and_then(|header| header.to_str().ok());
     let auth_header = req
         .headers()
         .get(header::AUTHORIZATION)
         .and_then(|header| header.to_str().ok());
 
-    match auth_header {
-        Some(auth_header) if auth_header.starts_with("Bearer ") => {
-            let token = &auth_header[7..];
-            match decode_jwt(token) {
-                Ok(claims) => {
-                    req.extensions_mut().insert(claims);
-                    Ok(next.run(req).await)
-                }
-                Err(_) => Err(AppError::Unauthorized("Invalid token".to_string())),
+    let api_key = match (api_key_header, auth_header) {
+        (Some(key), _) => Some(key.trim().to_string()),
+        (None, Some(auth_header)) if auth_header.starts_with("Bearer ") && auth_header[7..].starts_with(API_KEY_PREFIX) => {
+            Some(auth_header[7..].to_string())
+        }
+        _ => None,
+    };
+
+    let claims = match (api_key, auth_header) {
+        (Some(key), _) => {
+            let claims = api_key_service::authenticate(&state.db, &key)
+                .await
+                .map_err(AppError::Database)?
+                .ok_or_else(|| AppError::Unauthorized("Invalid API key".to_string()))?;
+
+            let path = req.extensions().get::<MatchedPath>().map_or("", |path| path.as_str());
+            let Some(scope) = required_scope(req.method(), path) else {
+                return Err(AppError::Forbidden("This endpoint requires a login token".to_string()));
+            };
+            let granted = claims.scopes.as_ref().is_some_and(|scopes| scopes.iter().any(|s| s == scope.as_str()));
+            if !granted {
+                return Err(AppError::Forbidden(format!("API key lacks the {} scope", scope.as_str())));
             }
+            claims
         }
-        _ => Err(AppError::Unauthorized("Missing or invalid authorization header".to_string())),
+        (None, Some(auth_header)) if auth_header.starts_with("Bearer ") => decode_jwt(&auth_header[7..])
+            .map_err(|_| AppError::Unauthorized("Invalid token".to_string()))?,
+        _ => return Err(AppError::Unauthorized("Missing or invalid authorization header".to_string())),
+    };
+
+    req.extensions_mut().insert(claims);
+    Ok(next.run(req).await)
+}
+
+/// Scope an API key needs for a route, or `None` when only a login token will do
+/// (account settings, API key management, alerts and notifications).
+fn required_scope(method: &Method, path: &str) -> Option<ApiScope> {
+    if !path.starts_with("/links") && !path.starts_with("/tags") {
+        return None;
+    }
+    if path.ends_with("/alerts") {
+        return None;
+    }
+
+    let is_read = method == Method::GET;
+    if path.contains("/analytics") || path.ends_with("/live") {
+        return is_read.then_some(ApiScope::AnalyticsRead);
     }
+    Some(if is_read { ApiScope::LinksRead } else { ApiScope::LinksWrite })
 }
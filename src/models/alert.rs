@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertKind {
+    /// The link's total clicks reached `threshold`.
+    ClickThreshold,
+    /// The current hour's clicks exceed `spike_factor` times the trailing hourly average.
+    ClickSpike,
+}
+
+impl AlertKind {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "click_threshold" => Some(AlertKind::ClickThreshold),
+            "click_spike" => Some(AlertKind::ClickSpike),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertKind::ClickThreshold => "click_threshold",
+            AlertKind::ClickSpike => "click_spike",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct AlertRule {
+    pub id: i64,
+    pub owner_id: i64,
+    pub link_id: i64,
+    pub kind: String,
+    pub threshold: Option<i64>,
+    pub spike_factor: Option<f64>,
+    pub window_hours: Option<i32>,
+    pub min_clicks: i32,
+    pub webhook_url: Option<String>,
+    pub is_active: bool,
+    pub last_triggered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Settings of a rule being created; which ones apply depends on `kind`.
+#[derive(Debug)]
+pub struct NewAlertRule {
+    pub link_id: i64,
+    pub kind: AlertKind,
+    pub threshold: Option<i64>,
+    pub spike_factor: Option<f64>,
+    pub window_hours: Option<i32>,
+    pub min_clicks: i32,
+    pub webhook_url: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Notification {
+    pub id: i64,
+    pub user_id: i64,
+    pub alert_rule_id: Option<i64>,
+    pub link_id: Option<i64>,
+    pub kind: String,
+    pub message: String,
+    pub clicks: i64,
+    pub created_at: DateTime<Utc>,
+    pub read_at: Option<DateTime<Utc>>,
+}
+
+/// A notification just written by the evaluator, with where else to deliver it.
+#[derive(Debug, FromRow)]
+pub struct FiredAlert {
+    pub notification_id: i64,
+    pub alert_rule_id: i64,
+    pub link_id: i64,
+    pub short_code: String,
+    pub kind: String,
+    pub message: String,
+    pub clicks: i64,
+    pub created_at: DateTime<Utc>,
+    pub webhook_url: Option<String>,
+}
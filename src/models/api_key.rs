@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use chrono::{DateTime, Utc};
+
+/// What an API key may do. Requests authenticated with a login token are not limited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiScope {
+    LinksRead,
+    LinksWrite,
+    AnalyticsRead,
+}
+
+impl ApiScope {
+    pub const ALL: [ApiScope; 3] = [ApiScope::LinksRead, ApiScope::LinksWrite, ApiScope::AnalyticsRead];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "links:read" => Some(ApiScope::LinksRead),
+            "links:write" => Some(ApiScope::LinksWrite),
+            "analytics:read" => Some(ApiScope::AnalyticsRead),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiScope::LinksRead => "links:read",
+            ApiScope::LinksWrite => "links:write",
+            ApiScope::AnalyticsRead => "analytics:read",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct ApiKey {
+    pub id: i64,
+    pub user_id: i64,
+    pub name: String,
+    pub prefix: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A usable key looked up by its hash, with the role of the user it acts as.
+#[derive(Debug, FromRow)]
+pub struct ActiveApiKey {
+    pub id: i64,
+    pub user_id: i64,
+    pub role: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
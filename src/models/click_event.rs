@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use chrono::{DateTime, NaiveDate, Utc};
+
+#[allow(dead_code)]
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct ClickEvent {
+    pub id: i64,
+    pub link_id: i64,
+    pub clicked_at: DateTime<Utc>,
+    pub referrer_host: Option<String>,
+    pub user_agent: Option<String>,
+    pub country: Option<String>,
+    pub ip_hash: Option<String>,
+    pub is_bot: bool,
+}
+
+/// A click waiting in the event writer's queue.
+#[derive(Debug, Clone)]
+pub struct NewClickEvent {
+    pub link_id: i64,
+    pub clicked_at: DateTime<Utc>,
+    pub referrer_host: Option<String>,
+    pub user_agent: Option<String>,
+    pub country: Option<String>,
+    pub ip_hash: Option<String>,
+    pub is_bot: bool,
+}
+
+/// A raw click event removed by the retention job, before it is rolled up.
+#[derive(Debug, FromRow)]
+pub struct PrunedClickEvent {
+    pub link_id: i64,
+    pub clicked_at: DateTime<Utc>,
+    pub referrer_host: Option<String>,
+    pub user_agent: Option<String>,
+    pub is_bot: bool,
+}
+
+/// Pruned click events sharing a link, UTC hour and breakdown values. The user
+/// agent is kept only as the browser, OS and device class parsed from it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClickRollupKey {
+    pub link_id: i64,
+    pub hour: DateTime<Utc>,
+    /// Normalized referrer host; `None` for direct visits.
+    pub referrer_host: Option<String>,
+    /// `None` when the event had no user agent, like the three fields below.
+    pub browser: Option<String>,
+    pub browser_version: Option<String>,
+    pub os: Option<String>,
+    pub device: Option<String>,
+    pub is_bot: bool,
+}
+
+/// Which click events an analytics query looks at: all of an owner's links,
+/// optionally narrowed to one link or one tag, between two local dates.
+#[derive(Debug, Clone)]
+pub struct ClickEventFilter {
+    pub owner_id: i64,
+    pub link_id: Option<i64>,
+    pub tag_id: Option<i64>,
+    pub from_date: NaiveDate,
+    pub to_date: NaiveDate,
+    /// IANA name the dates are interpreted in.
+    pub timezone: String,
+    /// Count hits flagged as bots as well as human clicks.
+    pub include_bots: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakdownDimension {
+    Referrers,
+    Devices,
+    Browsers,
+    Os,
+}
+
+impl BreakdownDimension {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BreakdownDimension::Referrers => "referrers",
+            BreakdownDimension::Devices => "devices",
+            BreakdownDimension::Browsers => "browsers",
+            BreakdownDimension::Os => "os",
+        }
+    }
+}
+
+/// Number of click events sharing one column value. For raw events that is the
+/// referrer host or the user agent; for rollups it is already the breakdown label.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct DimensionCount {
+    pub value: Option<String>,
+    pub clicks: i64,
+    pub rolled_up: bool,
+}
+
+/// What happens to click events older than the retention period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetentionMode {
+    /// Fold them into `click_event_rollups`, then delete them.
+    Delete,
+    /// Keep them but drop the IP hash.
+    Anonymize,
+}
+
+impl RetentionMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "delete" => Some(RetentionMode::Delete),
+            "anonymize" => Some(RetentionMode::Anonymize),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RetentionMode::Delete => "delete",
+            RetentionMode::Anonymize => "anonymize",
+        }
+    }
+}
+
+/// Click events older than a cutoff, as a retention run would find them.
+#[derive(Debug, FromRow)]
+pub struct RetentionCandidates {
+    pub events: i64,
+    pub with_ip_hash: i64,
+    /// Upper bound on the rollup rows the events would be folded into: events
+    /// sharing a link, hour, referrer and user agent always share a row.
+    pub rollup_rows: i64,
+    pub links: i64,
+    pub oldest_clicked_at: Option<DateTime<Utc>>,
+}
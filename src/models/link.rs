@@ -1,8 +1,8 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Link {
     pub id: i64,
     pub owner_id: Option<i64>,
@@ -11,6 +11,72 @@ pub struct Link {
     pub title: Option<String>,
     pub click_count: Option<i64>,
     pub is_active: Option<bool>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub max_clicks: Option<i64>,
+    pub password_hash: Option<String>,
+    pub redirect_type: i16,
+    pub cache_max_age: Option<i32>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
+
+/// Column values for a link row that is about to be inserted.
+#[derive(Debug, Clone)]
+pub struct NewLink {
+    pub id: i64,
+    pub owner_id: Option<i64>,
+    pub original_url: String,
+    pub short_code: String,
+    pub title: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub max_clicks: Option<i64>,
+    pub password_hash: Option<String>,
+    pub redirect_type: i16,
+    pub cache_max_age: Option<i32>,
+}
+
+/// Fields an owner may change on an existing link; `None` leaves the column as
+/// is, and `Some(None)` clears a nullable one.
+#[derive(Debug, Clone, Default)]
+pub struct LinkChanges {
+    pub original_url: Option<String>,
+    pub title: Option<Option<String>>,
+    pub is_active: Option<bool>,
+    pub redirect_type: Option<i16>,
+    pub cache_max_age: Option<Option<i32>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkStatusFilter {
+    All,
+    Active,
+    Inactive,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkSortField {
+    Created,
+    Updated,
+    Clicks,
+}
+
+/// Position after the last row of a page: the sort value (microseconds for
+/// timestamps, the count for clicks) and the link id as a tie-breaker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinkCursor {
+    pub sort_value: i64,
+    pub id: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct LinkSearch {
+    pub query: Option<String>,
+    pub status: LinkStatusFilter,
+    pub created_from: Option<NaiveDate>,
+    pub created_to: Option<NaiveDate>,
+    pub tag_id: Option<i64>,
+    pub sort: LinkSortField,
+    pub descending: bool,
+    pub cursor: Option<LinkCursor>,
+    pub limit: i64,
+}
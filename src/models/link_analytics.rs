@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
-use chrono::NaiveDate;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 
 #[allow(dead_code)]
 #[derive(Debug, Serialize, Deserialize, FromRow)]
@@ -11,8 +11,79 @@ pub struct LinkAnalytics {
 	pub clicks: i64,
 }
 
+/// Clicks buffered for one link in one UTC hour, waiting to be written to Postgres.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClickDelta {
+	pub link_id: i64,
+	pub hour: DateTime<Utc>,
+	pub clicks: i32,
+	/// Bot hits, which never count towards `links.click_count`.
+	pub bot_clicks: i32,
+}
+
+/// Visitor HyperLogLog of one link in one UTC hour, as stored by Redis.
+#[derive(Debug, Clone)]
+pub struct VisitorSketch {
+	pub link_id: i64,
+	pub hour: DateTime<Utc>,
+	pub visitors: Vec<u8>,
+}
+
+/// Union of one link's hourly visitor sketches over a UTC day.
+#[derive(Debug, Clone)]
+pub struct DailyVisitorSketch {
+	pub link_id: i64,
+	pub day: NaiveDate,
+	pub visitors: Vec<u8>,
+}
+
+/// A link and UTC day whose hourly visitor sketches have no daily union yet.
+#[derive(Debug, FromRow)]
+pub struct VisitorDay {
+	pub link_id: i64,
+	pub day: NaiveDate,
+}
+
+/// One hourly or daily visitor sketch, labelled with the local bucket it falls in.
+#[derive(Debug, FromRow)]
+pub struct PeriodVisitorSketch {
+	pub period: NaiveDateTime,
+	pub visitors: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnalyticsGranularity {
+	Hour,
+	Day,
+	Week,
+	Month,
+}
+
+impl AnalyticsGranularity {
+	/// Unit name understood by Postgres `date_trunc` and interval literals.
+	pub fn as_unit(&self) -> &'static str {
+		match self {
+			AnalyticsGranularity::Hour => "hour",
+			AnalyticsGranularity::Day => "day",
+			AnalyticsGranularity::Week => "week",
+			AnalyticsGranularity::Month => "month",
+		}
+	}
+}
+
+/// Clicks in one bucket of a time series; `period` is the local start of the bucket.
 #[derive(Debug, Serialize, Deserialize, FromRow)]
-pub struct DailyClickTotal {
-	pub date: NaiveDate,
-	pub total_clicks: i64,
-}
\ No newline at end of file
+pub struct ClickSeriesPoint {
+	pub period: NaiveDateTime,
+	pub clicks: i64,
+}
+
+/// Clicks of one link in one bucket, as streamed to analytics exports.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct LinkClickSeriesRow {
+	pub link_id: i64,
+	pub short_code: String,
+	pub period: NaiveDateTime,
+	pub clicks: i64,
+	pub bot_clicks: i64,
+}
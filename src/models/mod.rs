@@ -2,3 +2,7 @@ pub mod user;
 pub mod link;
 pub mod link_analytics;
 pub mod refresh_tokens;
+pub mod tag;
+pub mod click_event;
+pub mod alert;
+pub mod api_key;
@@ -12,4 +12,19 @@ pub struct RefreshToken {
     pub expires_at: DateTime<Utc>,
     pub revoked_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
+    /// Shared by every token rotated from the same login.
+    pub family_id: String,
+    /// The token issued when this one was used to refresh.
+    pub replaced_by_id: Option<i64>,
+}
+
+/// Outcome of exchanging a refresh token for a new one.
+#[derive(Debug)]
+pub enum RefreshRotation {
+    /// The presented token was live; it is now revoked and points at its replacement.
+    Rotated,
+    /// The presented token had already been used or revoked, so the whole family was revoked.
+    Reused { user_id: i64, family_id: String, revoked: u64 },
+    /// Unknown, expired or issued to someone else.
+    Invalid,
 }
@@ -10,6 +10,7 @@ pub struct User {
     pub email: Option<String>,
     pub role: String,
     pub is_active: bool,
+    pub timezone: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
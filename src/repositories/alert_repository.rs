@@ -0,0 +1,206 @@
+use sqlx::{PgPool, Error};
+use crate::models::alert::{AlertRule, FiredAlert, NewAlertRule, Notification};
+
+pub async fn create(pool: &PgPool, owner_id: i64, rule: &NewAlertRule) -> Result<AlertRule, Error> {
+    sqlx::query_as!(
+        AlertRule,
+        r#"
+        INSERT INTO alert_rules (owner_id, link_id, kind, threshold, spike_factor, window_hours, min_clicks, webhook_url)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING id, owner_id, link_id, kind, threshold, spike_factor, window_hours, min_clicks, webhook_url,
+                  is_active, last_triggered_at, created_at
+        "#,
+        owner_id,
+        rule.link_id,
+        rule.kind.as_str(),
+        rule.threshold,
+        rule.spike_factor,
+        rule.window_hours,
+        rule.min_clicks,
+        rule.webhook_url
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// The owner's rules, optionally only those of one link.
+pub async fn get_by_owner(pool: &PgPool, owner_id: i64, link_id: Option<i64>) -> Result<Vec<AlertRule>, Error> {
+    sqlx::query_as!(
+        AlertRule,
+        r#"
+        SELECT id, owner_id, link_id, kind, threshold, spike_factor, window_hours, min_clicks, webhook_url,
+               is_active, last_triggered_at, created_at
+        FROM alert_rules
+        WHERE owner_id = $1 AND ($2::BIGINT IS NULL OR link_id = $2)
+        ORDER BY id
+        "#,
+        owner_id,
+        link_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn delete_by_owner(pool: &PgPool, rule_id: i64, owner_id: i64) -> Result<bool, Error> {
+    let result = sqlx::query!("DELETE FROM alert_rules WHERE id = $1 AND owner_id = $2", rule_id, owner_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Marks every active threshold rule whose link reached its threshold as
+/// triggered and writes its notification, in one statement so a rule fires
+/// once even with several evaluators running.
+pub async fn fire_threshold_alerts(pool: &PgPool) -> Result<Vec<FiredAlert>, Error> {
+    sqlx::query_as!(
+        FiredAlert,
+        r#"
+        WITH fired AS (
+            UPDATE alert_rules r
+            SET last_triggered_at = NOW()
+            FROM links l
+            WHERE l.id = r.link_id
+              AND r.kind = 'click_threshold'
+              AND r.is_active
+              AND r.last_triggered_at IS NULL
+              AND COALESCE(l.click_count, 0) >= r.threshold
+            RETURNING r.id, r.owner_id, r.link_id, r.kind, r.threshold, r.webhook_url, l.short_code,
+                      COALESCE(l.click_count, 0) AS clicks
+        ),
+        inserted AS (
+            INSERT INTO notifications (user_id, alert_rule_id, link_id, kind, message, clicks)
+            SELECT owner_id, id, link_id, kind,
+                   format('Link /%s passed %s clicks (now %s)', short_code, threshold, clicks),
+                   clicks
+            FROM fired
+            RETURNING id, user_id, alert_rule_id, link_id, kind, message, clicks, created_at
+        )
+        SELECT n.id AS notification_id, f.id AS alert_rule_id, f.link_id, f.short_code,
+               n.kind, n.message, n.clicks, n.created_at, f.webhook_url
+        FROM inserted n
+        JOIN fired f ON f.id = n.alert_rule_id
+        "#
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Same as `fire_threshold_alerts` for spike rules: the current UTC hour's
+/// clicks are compared with the average of the `window_hours` hours before it,
+/// hours without clicks counting as zero. A rule fires at most once per hour.
+pub async fn fire_spike_alerts(pool: &PgPool) -> Result<Vec<FiredAlert>, Error> {
+    sqlx::query_as!(
+        FiredAlert,
+        r#"
+        WITH candidates AS (
+            SELECT r.id, r.owner_id, r.link_id, r.kind, r.spike_factor, r.window_hours, r.min_clicks, r.webhook_url,
+                   l.short_code,
+                   COALESCE(cur.clicks, 0)::BIGINT AS clicks,
+                   COALESCE(prev.total, 0)::DOUBLE PRECISION / r.window_hours AS baseline
+            FROM alert_rules r
+            JOIN links l ON l.id = r.link_id
+            LEFT JOIN link_click_hours cur
+                ON cur.link_id = r.link_id AND cur.hour = date_trunc('hour', NOW(), 'UTC')
+            LEFT JOIN LATERAL (
+                SELECT SUM(h.clicks) AS total
+                FROM link_click_hours h
+                WHERE h.link_id = r.link_id
+                  AND h.hour >= date_trunc('hour', NOW(), 'UTC') - make_interval(hours => r.window_hours)
+                  AND h.hour < date_trunc('hour', NOW(), 'UTC')
+            ) prev ON TRUE
+            WHERE r.kind = 'click_spike'
+              AND r.is_active
+              AND (r.last_triggered_at IS NULL OR r.last_triggered_at < date_trunc('hour', NOW(), 'UTC'))
+        ),
+        fired AS (
+            UPDATE alert_rules r
+            SET last_triggered_at = NOW()
+            FROM candidates c
+            WHERE r.id = c.id
+              AND (r.last_triggered_at IS NULL OR r.last_triggered_at < date_trunc('hour', NOW(), 'UTC'))
+              AND c.clicks >= GREATEST(c.min_clicks, 1)
+              AND c.clicks > c.spike_factor * c.baseline
+            RETURNING c.id, c.owner_id, c.link_id, c.kind, c.short_code, c.clicks, c.baseline, c.window_hours,
+                      c.webhook_url
+        ),
+        inserted AS (
+            INSERT INTO notifications (user_id, alert_rule_id, link_id, kind, message, clicks)
+            SELECT owner_id, id, link_id, kind,
+                   format('Link /%s has %s clicks this hour, against an average of %s per hour over the previous %s hours',
+                          short_code, clicks, round(baseline::NUMERIC, 1), window_hours),
+                   clicks
+            FROM fired
+            RETURNING id, user_id, alert_rule_id, link_id, kind, message, clicks, created_at
+        )
+        SELECT n.id AS notification_id, f.id AS alert_rule_id, f.link_id, f.short_code,
+               n.kind, n.message, n.clicks, n.created_at, f.webhook_url
+        FROM inserted n
+        JOIN fired f ON f.id = n.alert_rule_id
+        "#
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Newest first; `before_id` pages backwards through older notifications.
+pub async fn get_notifications(
+    pool: &PgPool,
+    user_id: i64,
+    unread_only: bool,
+    before_id: Option<i64>,
+    limit: i64,
+) -> Result<Vec<Notification>, Error> {
+    sqlx::query_as!(
+        Notification,
+        r#"
+        SELECT id, user_id, alert_rule_id, link_id, kind, message, clicks, created_at, read_at
+        FROM notifications
+        WHERE user_id = $1
+          AND (NOT $2 OR read_at IS NULL)
+          AND ($3::BIGINT IS NULL OR id < $3)
+        ORDER BY id DESC
+        LIMIT $4
+        "#,
+        user_id,
+        unread_only,
+        before_id,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn count_unread(pool: &PgPool, user_id: i64) -> Result<i64, Error> {
+    sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM notifications WHERE user_id = $1 AND read_at IS NULL"#,
+        user_id
+    )
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn mark_read(pool: &PgPool, notification_id: i64, user_id: i64) -> Result<Option<Notification>, Error> {
+    sqlx::query_as!(
+        Notification,
+        r#"
+        UPDATE notifications
+        SET read_at = COALESCE(read_at, NOW())
+        WHERE id = $1 AND user_id = $2
+        RETURNING id, user_id, alert_rule_id, link_id, kind, message, clicks, created_at, read_at
+        "#,
+        notification_id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn mark_all_read(pool: &PgPool, user_id: i64) -> Result<u64, Error> {
+    let result = sqlx::query!(
+        "UPDATE notifications SET read_at = NOW() WHERE user_id = $1 AND read_at IS NULL",
+        user_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,88 @@
+use sqlx::{PgPool, Error};
+use chrono::{DateTime, Utc};
+use crate::models::api_key::{ActiveApiKey, ApiKey};
+
+pub async fn create(
+    pool: &PgPool,
+    user_id: i64,
+    name: &str,
+    prefix: &str,
+    key_hash: &str,
+    scopes: &[String],
+    expires_at: Option<DateTime<Utc>>,
+) -> Result<ApiKey, Error> {
+    sqlx::query_as!(
+        ApiKey,
+        r#"
+        INSERT INTO api_keys (user_id, name, prefix, key_hash, scopes, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id, user_id, name, prefix, scopes, expires_at, last_used_at, revoked_at, created_at
+        "#,
+        user_id,
+        name,
+        prefix,
+        key_hash,
+        scopes,
+        expires_at
+    )
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn get_by_user(pool: &PgPool, user_id: i64) -> Result<Vec<ApiKey>, Error> {
+    sqlx::query_as!(
+        ApiKey,
+        r#"
+        SELECT id, user_id, name, prefix, scopes, expires_at, last_used_at, revoked_at, created_at
+        FROM api_keys
+        WHERE user_id = $1
+        ORDER BY id DESC
+        "#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Revokes one of the user's keys; `None` when it does not exist. Revoking twice keeps the first time.
+pub async fn revoke_by_user(pool: &PgPool, key_id: i64, user_id: i64) -> Result<Option<ApiKey>, Error> {
+    sqlx::query_as!(
+        ApiKey,
+        r#"
+        UPDATE api_keys
+        SET revoked_at = COALESCE(revoked_at, NOW())
+        WHERE id = $1 AND user_id = $2
+        RETURNING id, user_id, name, prefix, scopes, expires_at, last_used_at, revoked_at, created_at
+        "#,
+        key_id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// The key with this hash if it is neither revoked nor expired and its user is active.
+pub async fn find_active_by_hash(pool: &PgPool, key_hash: &str) -> Result<Option<ActiveApiKey>, Error> {
+    sqlx::query_as!(
+        ActiveApiKey,
+        r#"
+        SELECT k.id, k.user_id, u.role, k.scopes, k.expires_at, k.last_used_at, k.created_at
+        FROM api_keys k
+        JOIN users u ON u.id = k.user_id
+        WHERE k.key_hash = $1
+          AND k.revoked_at IS NULL
+          AND (k.expires_at IS NULL OR k.expires_at > NOW())
+          AND u.is_active
+        "#,
+        key_hash
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn touch_last_used(pool: &PgPool, key_id: i64) -> Result<(), Error> {
+    sqlx::query!("UPDATE api_keys SET last_used_at = NOW() WHERE id = $1", key_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,269 @@
+use sqlx::{PgPool, Error, Postgres, QueryBuilder, Transaction};
+use chrono::{DateTime, Utc};
+use crate::models::click_event::{
+    BreakdownDimension, ClickEventFilter, ClickRollupKey, DimensionCount, NewClickEvent, PrunedClickEvent,
+    RetentionCandidates,
+};
+
+/// Inserts a batch of click events in one statement. Events for links that
+/// no longer exist are dropped.
+pub async fn insert_many(pool: &PgPool, events: &[NewClickEvent]) -> Result<u64, Error> {
+    let link_ids: Vec<i64> = events.iter().map(|e| e.link_id).collect();
+    let clicked_ats: Vec<DateTime<Utc>> = events.iter().map(|e| e.clicked_at).collect();
+    let referrer_hosts: Vec<Option<String>> = events.iter().map(|e| e.referrer_host.clone()).collect();
+    let user_agents: Vec<Option<String>> = events.iter().map(|e| e.user_agent.clone()).collect();
+    let countries: Vec<Option<String>> = events.iter().map(|e| e.country.clone()).collect();
+    let ip_hashes: Vec<Option<String>> = events.iter().map(|e| e.ip_hash.clone()).collect();
+    let is_bots: Vec<bool> = events.iter().map(|e| e.is_bot).collect();
+
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO click_events (link_id, clicked_at, referrer_host, user_agent, country, ip_hash, is_bot)
+        SELECT e.link_id, e.clicked_at, e.referrer_host, e.user_agent, e.country, e.ip_hash, e.is_bot
+        FROM UNNEST($1::BIGINT[], $2::TIMESTAMPTZ[], $3::VARCHAR[], $4::VARCHAR[], $5::CHAR(2)[], $6::CHAR(64)[], $7::BOOLEAN[])
+            AS e(link_id, clicked_at, referrer_host, user_agent, country, ip_hash, is_bot)
+        JOIN links l ON l.id = e.link_id
+        "#,
+        &link_ids,
+        &clicked_ats,
+        &referrer_hosts as &[Option<String>],
+        &user_agents as &[Option<String>],
+        &countries as &[Option<String>],
+        &ip_hashes as &[Option<String>],
+        &is_bots
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Where click events live: raw rows, and hourly rollups of rows pruned by the retention job.
+#[derive(Clone, Copy)]
+enum EventSource {
+    Raw,
+    Rollup,
+}
+
+impl EventSource {
+    fn table(self) -> &'static str {
+        match self {
+            EventSource::Raw => "click_events",
+            EventSource::Rollup => "click_event_rollups",
+        }
+    }
+
+    fn time_column(self) -> &'static str {
+        match self {
+            EventSource::Raw => "e.clicked_at",
+            EventSource::Rollup => "e.hour",
+        }
+    }
+
+    fn clicks(self) -> &'static str {
+        match self {
+            EventSource::Raw => "COUNT(*)",
+            EventSource::Rollup => "SUM(e.clicks)",
+        }
+    }
+}
+
+fn push_event_filters<'a>(builder: &mut QueryBuilder<'a, Postgres>, filter: &'a ClickEventFilter, source: EventSource) {
+    builder
+        .push(format!(" FROM {} e JOIN links l ON l.id = e.link_id WHERE l.owner_id = ", source.table()))
+        .push_bind(filter.owner_id);
+
+    if let Some(link_id) = filter.link_id {
+        builder.push(" AND e.link_id = ").push_bind(link_id);
+    }
+    if let Some(tag_id) = filter.tag_id {
+        builder
+            .push(" AND EXISTS (SELECT 1 FROM link_tags lt WHERE lt.link_id = e.link_id AND lt.tag_id = ")
+            .push_bind(tag_id)
+            .push(")");
+    }
+
+    let time_column = source.time_column();
+    builder
+        .push(format!(" AND {time_column} >= "))
+        .push_bind(filter.from_date)
+        .push("::DATE::TIMESTAMP AT TIME ZONE ")
+        .push_bind(filter.timezone.as_str())
+        .push(format!(" AND {time_column} < ("))
+        .push_bind(filter.to_date)
+        .push("::DATE + 1)::TIMESTAMP AT TIME ZONE ")
+        .push_bind(filter.timezone.as_str());
+
+    if !filter.include_bots {
+        builder.push(" AND NOT e.is_bot");
+    }
+}
+
+/// Click events grouped by the column behind a breakdown. Raw events give the
+/// referrer host, or the user agent for everything parsed from it; rolled-up
+/// events, placed by their UTC hour, give the stored label directly.
+pub async fn count_by_dimension(
+    pool: &PgPool,
+    filter: &ClickEventFilter,
+    dimension: BreakdownDimension,
+    with_versions: bool,
+) -> Result<Vec<DimensionCount>, Error> {
+    let raw_column = match dimension {
+        BreakdownDimension::Referrers => "e.referrer_host",
+        BreakdownDimension::Devices | BreakdownDimension::Browsers | BreakdownDimension::Os => "e.user_agent",
+    };
+    let rollup_column = match dimension {
+        BreakdownDimension::Referrers => "e.referrer_host",
+        BreakdownDimension::Devices => "e.device",
+        BreakdownDimension::Os => "e.os",
+        BreakdownDimension::Browsers if with_versions => "COALESCE(e.browser || ' ' || e.browser_version, e.browser)",
+        BreakdownDimension::Browsers => "e.browser",
+    };
+
+    let mut builder = QueryBuilder::<Postgres>::new(
+        "SELECT value, SUM(clicks)::BIGINT AS clicks, rolled_up FROM (",
+    );
+    for (i, source) in [EventSource::Raw, EventSource::Rollup].into_iter().enumerate() {
+        if i > 0 {
+            builder.push(" UNION ALL ");
+        }
+        let (column, rolled_up) = match source {
+            EventSource::Raw => (raw_column, false),
+            EventSource::Rollup => (rollup_column, true),
+        };
+        builder.push(format!("SELECT {column} AS value, {} AS clicks, {rolled_up} AS rolled_up", source.clicks()));
+        push_event_filters(&mut builder, filter, source);
+        builder.push(" GROUP BY 1");
+    }
+    builder.push(") counts GROUP BY value, rolled_up");
+
+    builder.build_query_as::<DimensionCount>().fetch_all(pool).await
+}
+
+/// What a retention run with this cutoff would touch.
+pub async fn find_retention_candidates(pool: &PgPool, cutoff: DateTime<Utc>) -> Result<RetentionCandidates, Error> {
+    sqlx::query_as!(
+        RetentionCandidates,
+        r#"
+        SELECT COUNT(*) AS "events!",
+               COUNT(ip_hash) AS "with_ip_hash!",
+               COUNT(DISTINCT (link_id, date_trunc('hour', clicked_at), referrer_host, user_agent, is_bot)) AS "rollup_rows!",
+               COUNT(DISTINCT link_id) AS "links!",
+               MIN(clicked_at) AS oldest_clicked_at
+        FROM click_events
+        WHERE clicked_at < $1
+        "#,
+        cutoff
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Moves up to `batch_size` events older than `cutoff` into `click_event_rollups`
+/// in one transaction, so an event is either still raw or already counted in a
+/// rollup. `summarize` groups the removed events into rollup rows. Returns the
+/// number of events removed and of rollup rows written.
+pub async fn roll_up_and_delete_before<F>(
+    pool: &PgPool,
+    cutoff: DateTime<Utc>,
+    batch_size: i64,
+    summarize: F,
+) -> Result<(i64, i64), Error>
+where
+    F: FnOnce(&[PrunedClickEvent]) -> Vec<(ClickRollupKey, i32)>,
+{
+    let mut tx: Transaction<'_, Postgres> = pool.begin().await?;
+
+    let events = sqlx::query_as!(
+        PrunedClickEvent,
+        r#"
+        DELETE FROM click_events
+        WHERE id IN (
+            SELECT id FROM click_events
+            WHERE clicked_at < $1
+            ORDER BY id
+            LIMIT $2
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING link_id, clicked_at, referrer_host, user_agent, is_bot
+        "#,
+        cutoff,
+        batch_size
+    )
+    .fetch_all(tx.as_mut())
+    .await?;
+    if events.is_empty() {
+        return Ok((0, 0));
+    }
+
+    let rollups = summarize(&events);
+    let mut link_ids = Vec::with_capacity(rollups.len());
+    let mut hours = Vec::with_capacity(rollups.len());
+    let mut referrer_hosts = Vec::with_capacity(rollups.len());
+    let mut browsers = Vec::with_capacity(rollups.len());
+    let mut browser_versions = Vec::with_capacity(rollups.len());
+    let mut oses = Vec::with_capacity(rollups.len());
+    let mut devices = Vec::with_capacity(rollups.len());
+    let mut is_bots = Vec::with_capacity(rollups.len());
+    let mut clicks = Vec::with_capacity(rollups.len());
+    for (key, count) in rollups {
+        link_ids.push(key.link_id);
+        hours.push(key.hour);
+        referrer_hosts.push(key.referrer_host);
+        browsers.push(key.browser);
+        browser_versions.push(key.browser_version);
+        oses.push(key.os);
+        devices.push(key.device);
+        is_bots.push(key.is_bot);
+        clicks.push(count);
+    }
+
+    let rollup_rows = sqlx::query!(
+        r#"
+        INSERT INTO click_event_rollups (link_id, hour, referrer_host, browser, browser_version, os, device, is_bot, clicks)
+        SELECT * FROM UNNEST(
+            $1::BIGINT[], $2::TIMESTAMPTZ[], $3::VARCHAR[], $4::VARCHAR[], $5::VARCHAR[], $6::VARCHAR[], $7::VARCHAR[],
+            $8::BOOLEAN[], $9::INT[]
+        )
+        ON CONFLICT ON CONSTRAINT click_event_rollups_key
+        DO UPDATE SET clicks = click_event_rollups.clicks + EXCLUDED.clicks
+        "#,
+        &link_ids,
+        &hours,
+        &referrer_hosts as &[Option<String>],
+        &browsers as &[Option<String>],
+        &browser_versions as &[Option<String>],
+        &oses as &[Option<String>],
+        &devices as &[Option<String>],
+        &is_bots,
+        &clicks
+    )
+    .execute(tx.as_mut())
+    .await?
+    .rows_affected();
+
+    tx.commit().await?;
+    Ok((events.len() as i64, rollup_rows as i64))
+}
+
+/// Clears the IP hash of up to `batch_size` events older than `cutoff`.
+pub async fn anonymize_before(pool: &PgPool, cutoff: DateTime<Utc>, batch_size: i64) -> Result<u64, Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE click_events SET ip_hash = NULL
+        WHERE id IN (
+            SELECT id FROM click_events
+            WHERE clicked_at < $1 AND ip_hash IS NOT NULL
+            ORDER BY id
+            LIMIT $2
+            FOR UPDATE SKIP LOCKED
+        )
+        "#,
+        cutoff,
+        batch_size
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
@@ -1,7 +1,13 @@
-use sqlx::{PgPool, Error, Postgres, Transaction};
-use chrono::NaiveDate;
-use crate::models::link::Link;
-use crate::models::link_analytics::DailyClickTotal;
+use futures_util::stream::BoxStream;
+use sqlx::{PgPool, Error, Postgres, QueryBuilder, Transaction};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use crate::models::link::{Link, LinkChanges, LinkSearch, LinkSortField, LinkStatusFilter, NewLink};
+use crate::models::click_event::ClickEventFilter;
+use crate::models::link_analytics::{
+    AnalyticsGranularity, ClickDelta, ClickSeriesPoint, DailyVisitorSketch, LinkClickSeriesRow, PeriodVisitorSketch,
+    VisitorDay, VisitorSketch,
+};
+use crate::models::tag::TagClickTotal;
 
 pub async fn next_link_id(pool: &PgPool) -> Result<i64, Error> {
     let id = sqlx::query_scalar!("SELECT nextval('links_id_seq') AS \"id!\"")
@@ -10,45 +16,110 @@ pub async fn next_link_id(pool: &PgPool) -> Result<i64, Error> {
     Ok(id)
 }
 
-pub async fn create_with_id(
-    pool: &PgPool,
-    id: i64,
-    owner_id: Option<i64>,
-    original_url: &str,
-    short_code: &str,
-    title: Option<String>,
-) -> Result<Link, Error> {
+pub async fn create_with_id(pool: &PgPool, new_link: &NewLink) -> Result<Link, Error> {
     sqlx::query_as!(
         Link,
         r#"
-        INSERT INTO links (id, owner_id, original_url, short_code, title)
-        VALUES ($1, $2, $3, $4, $5)
-        RETURNING id, owner_id, original_url, short_code, title, click_count, is_active, created_at, updated_at
+        INSERT INTO links (id, owner_id, original_url, short_code, title, expires_at, max_clicks, password_hash, redirect_type, cache_max_age)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        RETURNING id, owner_id, original_url, short_code, title, click_count, is_active, expires_at, max_clicks, password_hash, redirect_type, cache_max_age, created_at, updated_at
         "#,
-        id,
-        owner_id,
-        original_url,
-        short_code,
-        title
+        new_link.id,
+        new_link.owner_id,
+        new_link.original_url,
+        new_link.short_code,
+        new_link.title,
+        new_link.expires_at,
+        new_link.max_clicks,
+        new_link.password_hash,
+        new_link.redirect_type,
+        new_link.cache_max_age
     )
     .fetch_one(pool)
     .await
 }
 
+pub async fn next_link_ids(pool: &PgPool, count: i64) -> Result<Vec<i64>, Error> {
+    sqlx::query_scalar!(
+        "SELECT nextval('links_id_seq') AS \"id!\" FROM generate_series(1, $1::BIGINT)",
+        count
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Inserts many links in one statement. Rows that hit a unique constraint are
+/// skipped instead of failing the batch, so callers must check which ids came back.
+pub async fn create_many(pool: &PgPool, owner_id: Option<i64>, new_links: &[NewLink]) -> Result<Vec<Link>, Error> {
+    let ids: Vec<i64> = new_links.iter().map(|l| l.id).collect();
+    let original_urls: Vec<String> = new_links.iter().map(|l| l.original_url.clone()).collect();
+    let short_codes: Vec<String> = new_links.iter().map(|l| l.short_code.clone()).collect();
+    let titles: Vec<Option<String>> = new_links.iter().map(|l| l.title.clone()).collect();
+    let expires_ats: Vec<Option<DateTime<Utc>>> = new_links.iter().map(|l| l.expires_at).collect();
+    let max_clicks: Vec<Option<i64>> = new_links.iter().map(|l| l.max_clicks).collect();
+    let password_hashes: Vec<Option<String>> = new_links.iter().map(|l| l.password_hash.clone()).collect();
+    let redirect_types: Vec<i16> = new_links.iter().map(|l| l.redirect_type).collect();
+    let cache_max_ages: Vec<Option<i32>> = new_links.iter().map(|l| l.cache_max_age).collect();
+
+    sqlx::query_as!(
+        Link,
+        r#"
+        INSERT INTO links (id, owner_id, original_url, short_code, title, expires_at, max_clicks, password_hash, redirect_type, cache_max_age)
+        SELECT t.id, $2, t.original_url, t.short_code, t.title, t.expires_at, t.max_clicks, t.password_hash, t.redirect_type, t.cache_max_age
+        FROM UNNEST($1::BIGINT[], $3::TEXT[], $4::VARCHAR[], $5::VARCHAR[], $6::TIMESTAMPTZ[], $7::BIGINT[], $8::VARCHAR[], $9::SMALLINT[], $10::INTEGER[])
+            AS t(id, original_url, short_code, title, expires_at, max_clicks, password_hash, redirect_type, cache_max_age)
+        ON CONFLICT DO NOTHING
+        RETURNING id, owner_id, original_url, short_code, title, click_count, is_active, expires_at, max_clicks, password_hash, redirect_type, cache_max_age, created_at, updated_at
+        "#,
+        &ids,
+        owner_id,
+        &original_urls,
+        &short_codes,
+        &titles as &[Option<String>],
+        &expires_ats as &[Option<DateTime<Utc>>],
+        &max_clicks as &[Option<i64>],
+        &password_hashes as &[Option<String>],
+        &redirect_types,
+        &cache_max_ages as &[Option<i32>]
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn find_taken_short_codes(pool: &PgPool, short_codes: &[String]) -> Result<Vec<String>, Error> {
+    sqlx::query_scalar!(
+        "SELECT short_code FROM links WHERE short_code = ANY($1)",
+        short_codes
+    )
+    .fetch_all(pool)
+    .await
+}
+
 pub async fn find_by_short_code(pool: &PgPool, short_code: &str) -> Result<Option<Link>, Error> {
     sqlx::query_as!(
         Link,
-        "SELECT id, owner_id, original_url, short_code, title, click_count, is_active, created_at, updated_at FROM links WHERE short_code = $1",
+        "SELECT id, owner_id, original_url, short_code, title, click_count, is_active, expires_at, max_clicks, password_hash, redirect_type, cache_max_age, created_at, updated_at FROM links WHERE short_code = $1",
         short_code
     )
     .fetch_optional(pool)
     .await
 }
 
+pub async fn find_by_id_and_owner(pool: &PgPool, link_id: i64, owner_id: i64) -> Result<Option<Link>, Error> {
+    sqlx::query_as!(
+        Link,
+        "SELECT id, owner_id, original_url, short_code, title, click_count, is_active, expires_at, max_clicks, password_hash, redirect_type, cache_max_age, created_at, updated_at FROM links WHERE id = $1 AND owner_id = $2",
+        link_id,
+        owner_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
 pub async fn find_active_by_short_code(pool: &PgPool, short_code: &str) -> Result<Option<Link>, Error> {
     sqlx::query_as!(
         Link,
-        "SELECT id, owner_id, original_url, short_code, title, click_count, is_active, created_at, updated_at FROM links WHERE short_code = $1 AND (is_active IS NULL OR is_active = TRUE)",
+        "SELECT id, owner_id, original_url, short_code, title, click_count, is_active, expires_at, max_clicks, password_hash, redirect_type, cache_max_age, created_at, updated_at FROM links WHERE short_code = $1 AND (is_active IS NULL OR is_active = TRUE) AND (expires_at IS NULL OR expires_at > NOW()) AND (max_clicks IS NULL OR COALESCE(click_count, 0) < max_clicks)",
         short_code
     )
     .fetch_optional(pool)
@@ -62,7 +133,7 @@ pub async fn find_by_owner_and_original_url(
 ) -> Result<Option<Link>, Error> {
     sqlx::query_as!(
         Link,
-        "SELECT id, owner_id, original_url, short_code, title, click_count, is_active, created_at, updated_at FROM links WHERE owner_id = $1 AND original_url = $2 AND (is_active IS NULL OR is_active = TRUE)",
+        "SELECT id, owner_id, original_url, short_code, title, click_count, is_active, expires_at, max_clicks, password_hash, redirect_type, cache_max_age, created_at, updated_at FROM links WHERE owner_id = $1 AND original_url = $2 AND (is_active IS NULL OR is_active = TRUE)",
         owner_id,
         original_url
     )
@@ -70,36 +141,311 @@ pub async fn find_by_owner_and_original_url(
     .await
 }
 
+pub async fn find_active_by_owner_and_original_urls(
+    pool: &PgPool,
+    owner_id: i64,
+    original_urls: &[String],
+) -> Result<Vec<Link>, Error> {
+    sqlx::query_as!(
+        Link,
+        "SELECT id, owner_id, original_url, short_code, title, click_count, is_active, expires_at, max_clicks, password_hash, redirect_type, cache_max_age, created_at, updated_at FROM links WHERE owner_id = $1 AND original_url = ANY($2) AND (is_active IS NULL OR is_active = TRUE)",
+        owner_id,
+        original_urls
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Counts one click unless the link has expired or used up its click budget.
+/// Returns `false` when the click was refused.
 pub async fn increment_click_and_analytics(
     pool: &PgPool,
     link_id: i64,
-    date: NaiveDate,
-) -> Result<(), Error> {
+    hour: DateTime<Utc>,
+) -> Result<bool, Error> {
     let mut tx: Transaction<'_, Postgres> = pool.begin().await?;
 
-    sqlx::query!(
-        "UPDATE links SET click_count = click_count + 1 WHERE id = $1",
+    let updated = sqlx::query!(
+        "UPDATE links SET click_count = COALESCE(click_count, 0) + 1 WHERE id = $1 AND (expires_at IS NULL OR expires_at > NOW()) AND (max_clicks IS NULL OR COALESCE(click_count, 0) < max_clicks)",
         link_id
     )
     .execute(tx.as_mut())
     .await?;
 
+    if updated.rows_affected() == 0 {
+        tx.rollback().await?;
+        return Ok(false);
+    }
+
+    sqlx::query!(
+        "INSERT INTO link_click_hours (link_id, hour, clicks) VALUES ($1, $2, 1) ON CONFLICT (link_id, hour) DO UPDATE SET clicks = link_click_hours.clicks + 1",
+        link_id,
+        hour
+    )
+    .execute(tx.as_mut())
+    .await?;
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Counts one bot hit. Bot hits never touch `links.click_count` or a click budget.
+pub async fn increment_bot_clicks(pool: &PgPool, link_id: i64, hour: DateTime<Utc>) -> Result<(), Error> {
     sqlx::query!(
-        "INSERT INTO link_analytics (link_id, date, clicks) VALUES ($1, $2, 1) ON CONFLICT (link_id, date) DO UPDATE SET clicks = link_analytics.clicks + 1",
+        "INSERT INTO link_click_hours (link_id, hour, clicks, bot_clicks) VALUES ($1, $2, 0, 1) ON CONFLICT (link_id, hour) DO UPDATE SET bot_clicks = link_click_hours.bot_clicks + 1",
         link_id,
-        date
+        hour
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Applies one batch of buffered clicks in a single transaction. The batch is
+/// recorded under `flush_id` first; returns `false` if it had already been applied.
+pub async fn apply_click_deltas(pool: &PgPool, flush_id: &str, deltas: &[ClickDelta]) -> Result<bool, Error> {
+    let mut tx: Transaction<'_, Postgres> = pool.begin().await?;
+
+    let inserted = sqlx::query!(
+        "INSERT INTO click_flushes (flush_id) VALUES ($1) ON CONFLICT DO NOTHING",
+        flush_id
+    )
+    .execute(tx.as_mut())
+    .await?;
+
+    if inserted.rows_affected() == 0 {
+        tx.rollback().await?;
+        return Ok(false);
+    }
+
+    let link_ids: Vec<i64> = deltas.iter().map(|d| d.link_id).collect();
+    let hours: Vec<DateTime<Utc>> = deltas.iter().map(|d| d.hour).collect();
+    let clicks: Vec<i32> = deltas.iter().map(|d| d.clicks).collect();
+    let bot_clicks: Vec<i32> = deltas.iter().map(|d| d.bot_clicks).collect();
+
+    sqlx::query!(
+        r#"
+        UPDATE links
+        SET click_count = COALESCE(links.click_count, 0) + d.clicks
+        FROM (
+            SELECT link_id, SUM(clicks)::BIGINT AS clicks
+            FROM UNNEST($1::BIGINT[], $2::INT[]) AS t(link_id, clicks)
+            GROUP BY link_id
+        ) AS d
+        WHERE links.id = d.link_id
+        "#,
+        &link_ids,
+        &clicks
+    )
+    .execute(tx.as_mut())
+    .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO link_click_hours (link_id, hour, clicks, bot_clicks)
+        SELECT d.link_id, d.hour, SUM(d.clicks)::INT, SUM(d.bot_clicks)::INT
+        FROM UNNEST($1::BIGINT[], $2::TIMESTAMPTZ[], $3::INT[], $4::INT[]) AS d(link_id, hour, clicks, bot_clicks)
+        JOIN links l ON l.id = d.link_id
+        GROUP BY d.link_id, d.hour
+        ON CONFLICT (link_id, hour) DO UPDATE
+        SET clicks = link_click_hours.clicks + EXCLUDED.clicks,
+            bot_clicks = link_click_hours.bot_clicks + EXCLUDED.bot_clicks
+        "#,
+        &link_ids,
+        &hours,
+        &clicks,
+        &bot_clicks
     )
     .execute(tx.as_mut())
     .await?;
 
     tx.commit().await?;
+    Ok(true)
+}
+
+/// Saves the latest Redis copy of each hourly visitor sketch. A sketch only
+/// grows, so overwriting the stored one is safe to repeat.
+pub async fn store_visitor_sketches(pool: &PgPool, sketches: &[VisitorSketch]) -> Result<(), Error> {
+    let link_ids: Vec<i64> = sketches.iter().map(|s| s.link_id).collect();
+    let hours: Vec<DateTime<Utc>> = sketches.iter().map(|s| s.hour).collect();
+    let visitors: Vec<Vec<u8>> = sketches.iter().map(|s| s.visitors.clone()).collect();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO link_click_hours (link_id, hour, clicks, visitors)
+        SELECT s.link_id, s.hour, 0, s.visitors
+        FROM UNNEST($1::BIGINT[], $2::TIMESTAMPTZ[], $3::BYTEA[]) AS s(link_id, hour, visitors)
+        JOIN links l ON l.id = s.link_id
+        ON CONFLICT (link_id, hour) DO UPDATE SET visitors = EXCLUDED.visitors
+        "#,
+        &link_ids,
+        &hours,
+        &visitors
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Saves the merged daily visitor sketches; like the hourly ones they only grow.
+pub async fn store_daily_visitor_sketches(pool: &PgPool, sketches: &[DailyVisitorSketch]) -> Result<(), Error> {
+    let link_ids: Vec<i64> = sketches.iter().map(|s| s.link_id).collect();
+    let days: Vec<NaiveDate> = sketches.iter().map(|s| s.day).collect();
+    let visitors: Vec<Vec<u8>> = sketches.iter().map(|s| s.visitors.clone()).collect();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO link_visitor_days (link_id, day, visitors)
+        SELECT s.link_id, s.day, s.visitors
+        FROM UNNEST($1::BIGINT[], $2::DATE[], $3::BYTEA[]) AS s(link_id, day, visitors)
+        JOIN links l ON l.id = s.link_id
+        ON CONFLICT (link_id, day) DO UPDATE SET visitors = EXCLUDED.visitors
+        "#,
+        &link_ids,
+        &days,
+        &visitors
+    )
+    .execute(pool)
+    .await?;
     Ok(())
 }
 
-pub async fn get_all_by_user(pool: &PgPool, user_id: i64) -> Result<Vec<Link>, Error> {
+/// The stored hourly visitor sketches of one link over one UTC day.
+pub async fn get_hourly_visitor_sketches_of_day(pool: &PgPool, link_id: i64, day: NaiveDate) -> Result<Vec<Vec<u8>>, Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT visitors AS "visitors!"
+        FROM link_click_hours
+        WHERE link_id = $1
+            AND visitors IS NOT NULL
+            AND hour >= $2::DATE::TIMESTAMP AT TIME ZONE 'UTC'
+            AND hour < ($2::DATE + 1)::TIMESTAMP AT TIME ZONE 'UTC'
+        "#,
+        link_id,
+        day
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Link days that have hourly visitor sketches but no daily union, oldest first.
+pub async fn find_visitor_days_without_union(pool: &PgPool, limit: i64) -> Result<Vec<VisitorDay>, Error> {
+    sqlx::query_as!(
+        VisitorDay,
+        r#"
+        SELECT DISTINCT h.link_id, (h.hour AT TIME ZONE 'UTC')::DATE AS "day!"
+        FROM link_click_hours h
+        WHERE h.visitors IS NOT NULL
+            AND NOT EXISTS (
+                SELECT 1 FROM link_visitor_days d
+                WHERE d.link_id = h.link_id AND d.day = (h.hour AT TIME ZONE 'UTC')::DATE
+            )
+        ORDER BY 2, 1
+        LIMIT $1
+        "#,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+}
+
+fn push_search_filters<'a>(builder: &mut QueryBuilder<'a, Postgres>, owner_id: i64, search: &'a LinkSearch) {
+    builder.push(" WHERE owner_id = ").push_bind(owner_id);
+
+    if let Some(query) = search.query.as_deref() {
+        let pattern = format!(
+            "%{}%",
+            query.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+        );
+        builder
+            .push(" AND (original_url ILIKE ")
+            .push_bind(pattern.clone())
+            .push(" OR title ILIKE ")
+            .push_bind(pattern.clone())
+            .push(" OR short_code ILIKE ")
+            .push_bind(pattern)
+            .push(")");
+    }
+
+    match search.status {
+        LinkStatusFilter::All => {}
+        LinkStatusFilter::Active => {
+            builder.push(" AND (is_active IS NULL OR is_active = TRUE)");
+        }
+        LinkStatusFilter::Inactive => {
+            builder.push(" AND is_active = FALSE");
+        }
+    }
+
+    if let Some(from) = search.created_from {
+        builder.push(" AND created_at >= ").push_bind(from.and_time(NaiveTime::MIN).and_utc());
+    }
+    if let Some(to) = search.created_to.and_then(|to| to.succ_opt()) {
+        builder.push(" AND created_at < ").push_bind(to.and_time(NaiveTime::MIN).and_utc());
+    }
+
+    if let Some(tag_id) = search.tag_id {
+        builder
+            .push(" AND EXISTS (SELECT 1 FROM link_tags lt WHERE lt.link_id = links.id AND lt.tag_id = ")
+            .push_bind(tag_id)
+            .push(")");
+    }
+}
+
+/// One page of the owner's links plus the total number of links matching the filters.
+pub async fn search_by_user(pool: &PgPool, owner_id: i64, search: &LinkSearch) -> Result<(Vec<Link>, i64), Error> {
+    let mut count_builder = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM links");
+    push_search_filters(&mut count_builder, owner_id, search);
+    let total: i64 = count_builder.build_query_scalar().fetch_one(pool).await?;
+
+    let sort_column = match search.sort {
+        LinkSortField::Created => "created_at",
+        LinkSortField::Updated => "updated_at",
+        LinkSortField::Clicks => "COALESCE(click_count, 0)",
+    };
+    let (direction, comparison) = if search.descending { ("DESC", "<") } else { ("ASC", ">") };
+
+    let mut builder = QueryBuilder::<Postgres>::new(
+        "SELECT id, owner_id, original_url, short_code, title, click_count, is_active, expires_at, max_clicks, password_hash, redirect_type, cache_max_age, created_at, updated_at FROM links",
+    );
+    push_search_filters(&mut builder, owner_id, search);
+
+    if let Some(cursor) = search.cursor {
+        builder.push(format!(" AND ({sort_column}, id) {comparison} ("));
+        match search.sort {
+            LinkSortField::Clicks => {
+                builder.push_bind(cursor.sort_value);
+            }
+            LinkSortField::Created | LinkSortField::Updated => {
+                builder.push_bind(DateTime::<Utc>::from_timestamp_micros(cursor.sort_value));
+            }
+        }
+        builder.push(", ").push_bind(cursor.id).push(")");
+    }
+
+    builder
+        .push(format!(" ORDER BY {sort_column} {direction}, id {direction} LIMIT "))
+        .push_bind(search.limit);
+
+    let links = builder.build_query_as::<Link>().fetch_all(pool).await?;
+    Ok((links, total))
+}
+
+/// Every link of the owner, active or not, oldest first, read row by row.
+pub fn stream_by_owner(pool: &PgPool, owner_id: i64) -> BoxStream<'_, Result<Link, Error>> {
     sqlx::query_as!(
         Link,
-        "SELECT id, owner_id, original_url, short_code, title, click_count, is_active, created_at, updated_at FROM links WHERE owner_id = $1 ORDER BY created_at DESC",
+        "SELECT id, owner_id, original_url, short_code, title, click_count, is_active, expires_at, max_clicks, password_hash, redirect_type, cache_max_age, created_at, updated_at FROM links WHERE owner_id = $1 ORDER BY id",
+        owner_id
+    )
+    .fetch(pool)
+}
+
+/// The owner's trash: soft-deleted links, leaving out those an admin disabled.
+pub async fn get_inactive_by_user(pool: &PgPool, user_id: i64) -> Result<Vec<Link>, Error> {
+    sqlx::query_as!(
+        Link,
+        "SELECT id, owner_id, original_url, short_code, title, click_count, is_active, expires_at, max_clicks, password_hash, redirect_type, cache_max_age, created_at, updated_at FROM links WHERE owner_id = $1 AND is_active = FALSE AND NOT disabled_by_admin ORDER BY updated_at DESC",
         user_id
     )
     .fetch_all(pool)
@@ -109,12 +455,57 @@ pub async fn get_all_by_user(pool: &PgPool, user_id: i64) -> Result<Vec<Link>, E
 pub async fn get_all(pool: &PgPool) -> Result<Vec<Link>, Error> {
     sqlx::query_as!(
         Link,
-        "SELECT id, owner_id, original_url, short_code, title, click_count, is_active, created_at, updated_at FROM links ORDER BY created_at DESC"
+        "SELECT id, owner_id, original_url, short_code, title, click_count, is_active, expires_at, max_clicks, password_hash, redirect_type, cache_max_age, created_at, updated_at FROM links ORDER BY created_at DESC"
     )
     .fetch_all(pool)
     .await
 }
 
+/// Applies `changes` to the owner's link. Re-activating a link an admin
+/// disabled matches no row.
+pub async fn update_by_owner(
+    pool: &PgPool,
+    link_id: i64,
+    owner_id: i64,
+    changes: &LinkChanges,
+) -> Result<Option<Link>, Error> {
+    sqlx::query_as!(
+        Link,
+        r#"
+        UPDATE links
+        SET original_url = COALESCE($3, original_url),
+            title = CASE WHEN $4 THEN $5 ELSE title END,
+            is_active = COALESCE($6, is_active),
+            redirect_type = COALESCE($7, redirect_type),
+            cache_max_age = CASE WHEN $8 THEN $9 ELSE cache_max_age END,
+            updated_at = NOW()
+        WHERE id = $1 AND owner_id = $2 AND NOT (disabled_by_admin AND $6 IS TRUE)
+        RETURNING id, owner_id, original_url, short_code, title, click_count, is_active, expires_at, max_clicks, password_hash, redirect_type, cache_max_age, created_at, updated_at
+        "#,
+        link_id,
+        owner_id,
+        changes.original_url,
+        changes.title.is_some(),
+        changes.title.clone().flatten(),
+        changes.is_active,
+        changes.redirect_type,
+        changes.cache_max_age.is_some(),
+        changes.cache_max_age.flatten()
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn is_disabled_by_admin(pool: &PgPool, link_id: i64, owner_id: i64) -> Result<bool, Error> {
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM links WHERE id = $1 AND owner_id = $2 AND disabled_by_admin) AS "exists!""#,
+        link_id,
+        owner_id
+    )
+    .fetch_one(pool)
+    .await
+}
+
 pub async fn soft_delete_by_owner(
     pool: &PgPool,
     link_id: i64,
@@ -122,7 +513,7 @@ pub async fn soft_delete_by_owner(
 ) -> Result<Option<Link>, Error> {
     sqlx::query_as!(
         Link,
-        "UPDATE links SET is_active = FALSE, updated_at = NOW() WHERE id = $1 AND owner_id = $2 RETURNING id, owner_id, original_url, short_code, title, click_count, is_active, created_at, updated_at",
+        "UPDATE links SET is_active = FALSE, updated_at = NOW() WHERE id = $1 AND owner_id = $2 RETURNING id, owner_id, original_url, short_code, title, click_count, is_active, expires_at, max_clicks, password_hash, redirect_type, cache_max_age, created_at, updated_at",
         link_id,
         owner_id
     )
@@ -130,28 +521,289 @@ pub async fn soft_delete_by_owner(
     .await
 }
 
+pub async fn soft_delete_by_ids(pool: &PgPool, link_ids: &[i64]) -> Result<u64, Error> {
+    let result = sqlx::query!(
+        "UPDATE links SET is_active = FALSE, updated_at = NOW() WHERE id = ANY($1)",
+        link_ids
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
 pub async fn soft_delete_by_id(pool: &PgPool, link_id: i64) -> Result<Option<Link>, Error> {
     sqlx::query_as!(
         Link,
-        "UPDATE links SET is_active = FALSE, updated_at = NOW() WHERE id = $1 RETURNING id, owner_id, original_url, short_code, title, click_count, is_active, created_at, updated_at",
+        "UPDATE links SET is_active = FALSE, updated_at = NOW() WHERE id = $1 RETURNING id, owner_id, original_url, short_code, title, click_count, is_active, expires_at, max_clicks, password_hash, redirect_type, cache_max_age, created_at, updated_at",
+        link_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Takes a link down on an admin's behalf; unlike an owner's soft delete, it
+/// stays down until `restore_by_id`.
+pub async fn disable_by_id(pool: &PgPool, link_id: i64) -> Result<Option<Link>, Error> {
+    sqlx::query_as!(
+        Link,
+        "UPDATE links SET is_active = FALSE, disabled_by_admin = TRUE, updated_at = NOW() WHERE id = $1 RETURNING id, owner_id, original_url, short_code, title, click_count, is_active, expires_at, max_clicks, password_hash, redirect_type, cache_max_age, created_at, updated_at",
+        link_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Restores a link from the owner's trash; admin-disabled links are left alone.
+pub async fn restore_by_owner(
+    pool: &PgPool,
+    link_id: i64,
+    owner_id: i64,
+) -> Result<Option<Link>, Error> {
+    sqlx::query_as!(
+        Link,
+        "UPDATE links SET is_active = TRUE, updated_at = NOW() WHERE id = $1 AND owner_id = $2 AND is_active = FALSE AND NOT disabled_by_admin RETURNING id, owner_id, original_url, short_code, title, click_count, is_active, expires_at, max_clicks, password_hash, redirect_type, cache_max_age, created_at, updated_at",
+        link_id,
+        owner_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn restore_by_id(pool: &PgPool, link_id: i64) -> Result<Option<Link>, Error> {
+    sqlx::query_as!(
+        Link,
+        "UPDATE links SET is_active = TRUE, disabled_by_admin = FALSE, updated_at = NOW() WHERE id = $1 AND is_active = FALSE RETURNING id, owner_id, original_url, short_code, title, click_count, is_active, expires_at, max_clicks, password_hash, redirect_type, cache_max_age, created_at, updated_at",
         link_id
     )
     .fetch_optional(pool)
     .await
 }
 
-pub async fn get_daily_analytics_by_user(
+/// Clicks per bucket over the owner's links, narrowed to `filter.link_id` or
+/// `filter.tag_id` when set. Buckets are local to `filter.timezone` and empty
+/// ones are filled with zero; the first starts at the beginning of the hour,
+/// day, week or month containing `from_date`.
+pub async fn get_click_series(
+    pool: &PgPool,
+    filter: &ClickEventFilter,
+    granularity: AnalyticsGranularity,
+) -> Result<Vec<ClickSeriesPoint>, Error> {
+    sqlx::query_as!(
+        ClickSeriesPoint,
+        r#"
+        SELECT bucket AS "period!",
+               COALESCE(SUM(h.clicks + CASE WHEN $6 THEN h.bot_clicks ELSE 0 END), 0) AS "clicks!"
+        FROM generate_series(
+            date_trunc($5, $2::DATE::TIMESTAMP),
+            ($3::DATE + 1)::TIMESTAMP - INTERVAL '1 hour',
+            ('1 ' || $5)::INTERVAL
+        ) AS bucket
+        LEFT JOIN link_click_hours h
+            ON h.hour >= $2::DATE::TIMESTAMP AT TIME ZONE $4
+            AND h.hour < ($3::DATE + 1)::TIMESTAMP AT TIME ZONE $4
+            AND date_trunc($5, h.hour AT TIME ZONE $4) = bucket
+            AND h.link_id IN (
+                SELECT l.id FROM links l
+                WHERE l.owner_id = $1
+                    AND ($7::BIGINT IS NULL OR l.id = $7)
+                    AND ($8::BIGINT IS NULL OR EXISTS (SELECT 1 FROM link_tags lt WHERE lt.link_id = l.id AND lt.tag_id = $8))
+            )
+        GROUP BY bucket
+        ORDER BY bucket
+        "#,
+        filter.owner_id,
+        filter.from_date,
+        filter.to_date,
+        filter.timezone,
+        granularity.as_unit(),
+        filter.include_bots,
+        filter.link_id,
+        filter.tag_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Visitor sketches matching `filter`, labelled with the local bucket they fall
+/// in: hourly sketches for hourly buckets, otherwise the daily unions, whose UTC
+/// day is taken as the local one. At most `limit` rows are returned.
+pub async fn get_visitor_sketches(
+    pool: &PgPool,
+    filter: &ClickEventFilter,
+    granularity: AnalyticsGranularity,
+    limit: i64,
+) -> Result<Vec<PeriodVisitorSketch>, Error> {
+    if granularity == AnalyticsGranularity::Hour {
+        return sqlx::query_as!(
+            PeriodVisitorSketch,
+            r#"
+            SELECT date_trunc('hour', h.hour AT TIME ZONE $4) AS "period!",
+                   h.visitors AS "visitors!"
+            FROM link_click_hours h
+            JOIN links l ON l.id = h.link_id
+            WHERE l.owner_id = $1
+                AND h.visitors IS NOT NULL
+                AND h.hour >= $2::DATE::TIMESTAMP AT TIME ZONE $4
+                AND h.hour < ($3::DATE + 1)::TIMESTAMP AT TIME ZONE $4
+                AND ($5::BIGINT IS NULL OR h.link_id = $5)
+                AND ($6::BIGINT IS NULL OR EXISTS (SELECT 1 FROM link_tags lt WHERE lt.link_id = h.link_id AND lt.tag_id = $6))
+            LIMIT $7
+            "#,
+            filter.owner_id,
+            filter.from_date,
+            filter.to_date,
+            filter.timezone,
+            filter.link_id,
+            filter.tag_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await;
+    }
+
+    sqlx::query_as!(
+        PeriodVisitorSketch,
+        r#"
+        SELECT date_trunc($4, d.day::TIMESTAMP) AS "period!",
+               d.visitors
+        FROM link_visitor_days d
+        JOIN links l ON l.id = d.link_id
+        WHERE l.owner_id = $1
+            AND d.day >= $2
+            AND d.day <= $3
+            AND ($5::BIGINT IS NULL OR d.link_id = $5)
+            AND ($6::BIGINT IS NULL OR EXISTS (SELECT 1 FROM link_tags lt WHERE lt.link_id = d.link_id AND lt.tag_id = $6))
+        LIMIT $7
+        "#,
+        filter.owner_id,
+        filter.from_date,
+        filter.to_date,
+        granularity.as_unit(),
+        filter.link_id,
+        filter.tag_id,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Human and bot clicks per link and local bucket over the owner's links, read
+/// row by row. Only buckets with at least one hit are returned.
+pub fn stream_click_series_by_owner<'a>(
+    pool: &'a PgPool,
+    owner_id: i64,
+    from_date: NaiveDate,
+    to_date: NaiveDate,
+    timezone: &'a str,
+    tag_id: Option<i64>,
+    granularity: AnalyticsGranularity,
+) -> BoxStream<'a, Result<LinkClickSeriesRow, Error>> {
+    sqlx::query_as!(
+        LinkClickSeriesRow,
+        r#"
+        SELECT h.link_id,
+               l.short_code,
+               date_trunc($5, h.hour AT TIME ZONE $4) AS "period!",
+               SUM(h.clicks)::BIGINT AS "clicks!",
+               SUM(h.bot_clicks)::BIGINT AS "bot_clicks!"
+        FROM link_click_hours h
+        JOIN links l ON l.id = h.link_id
+        WHERE l.owner_id = $1
+            AND h.hour >= $2::DATE::TIMESTAMP AT TIME ZONE $4
+            AND h.hour < ($3::DATE + 1)::TIMESTAMP AT TIME ZONE $4
+            AND ($6::BIGINT IS NULL OR EXISTS (SELECT 1 FROM link_tags lt WHERE lt.link_id = h.link_id AND lt.tag_id = $6))
+        GROUP BY h.link_id, l.short_code, 3
+        HAVING SUM(h.clicks) + SUM(h.bot_clicks) > 0
+        ORDER BY h.link_id, 3
+        "#,
+        owner_id,
+        from_date,
+        to_date,
+        timezone,
+        granularity.as_unit(),
+        tag_id
+    )
+    .fetch(pool)
+}
+
+pub async fn get_clicks_total_by_link(
+    pool: &PgPool,
+    link_id: i64,
+    from_date: NaiveDate,
+    to_date: NaiveDate,
+    timezone: &str,
+    include_bots: bool,
+) -> Result<i64, Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT COALESCE(SUM(clicks + CASE WHEN $5 THEN bot_clicks ELSE 0 END), 0) AS "total!"
+        FROM link_click_hours
+        WHERE link_id = $1
+            AND hour >= $2::DATE::TIMESTAMP AT TIME ZONE $4
+            AND hour < ($3::DATE + 1)::TIMESTAMP AT TIME ZONE $4
+        "#,
+        link_id,
+        from_date,
+        to_date,
+        timezone,
+        include_bots
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Time of the first and last recorded click event of a link. Events already
+/// rolled up by the retention job only keep their hour.
+pub async fn get_click_time_bounds_by_link(
+    pool: &PgPool,
+    link_id: i64,
+) -> Result<(Option<DateTime<Utc>>, Option<DateTime<Utc>>), Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT LEAST(raw.first_click_at, rolled.first_click_at) AS first_click_at,
+               GREATEST(raw.last_click_at, rolled.last_click_at) AS last_click_at
+        FROM (SELECT MIN(clicked_at) AS first_click_at, MAX(clicked_at) AS last_click_at FROM click_events WHERE link_id = $1) raw,
+             (SELECT MIN(hour) AS first_click_at, MAX(hour) AS last_click_at FROM click_event_rollups WHERE link_id = $1) rolled
+        "#,
+        link_id
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok((row.first_click_at, row.last_click_at))
+}
+
+/// Click totals per tag, so tags can be compared as an analytics dimension.
+pub async fn get_tag_analytics_by_user(
     pool: &PgPool,
     owner_id: i64,
     from_date: NaiveDate,
     to_date: NaiveDate,
-) -> Result<Vec<DailyClickTotal>, Error> {
+    timezone: &str,
+    include_bots: bool,
+) -> Result<Vec<TagClickTotal>, Error> {
     sqlx::query_as!(
-        DailyClickTotal,
-        "SELECT la.date, COALESCE(SUM(la.clicks), 0) AS \"total_clicks!\" FROM link_analytics la JOIN links l ON l.id = la.link_id WHERE l.owner_id = $1 AND la.date BETWEEN $2 AND $3 GROUP BY la.date ORDER BY la.date",
+        TagClickTotal,
+        r#"
+        SELECT t.id AS tag_id,
+               t.name,
+               COUNT(DISTINCT lt.link_id) AS "link_count!",
+               COALESCE(SUM(h.clicks + CASE WHEN $5 THEN h.bot_clicks ELSE 0 END), 0) AS "total_clicks!"
+        FROM tags t
+        LEFT JOIN link_tags lt ON lt.tag_id = t.id
+        LEFT JOIN link_click_hours h
+            ON h.link_id = lt.link_id
+            AND h.hour >= $2::DATE::TIMESTAMP AT TIME ZONE $4
+            AND h.hour < ($3::DATE + 1)::TIMESTAMP AT TIME ZONE $4
+        WHERE t.owner_id = $1
+        GROUP BY t.id, t.name
+        ORDER BY "total_clicks!" DESC, LOWER(t.name)
+        "#,
         owner_id,
         from_date,
-        to_date
+        to_date,
+        timezone,
+        include_bots
     )
     .fetch_all(pool)
     .await
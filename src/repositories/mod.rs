@@ -1,2 +1,6 @@
 pub mod user_repository;
 pub mod link_repository;
+pub mod tag_repository;
+pub mod click_event_repository;
+pub mod alert_repository;
+pub mod api_key_repository;
@@ -0,0 +1,117 @@
+use sqlx::{PgPool, Error};
+use crate::models::tag::Tag;
+
+pub async fn create(
+    pool: &PgPool,
+    owner_id: i64,
+    name: &str,
+    color: Option<&str>,
+) -> Result<Tag, Error> {
+    sqlx::query_as!(
+        Tag,
+        "INSERT INTO tags (owner_id, name, color) VALUES ($1, $2, $3) RETURNING id, owner_id, name, color, created_at, updated_at",
+        owner_id,
+        name,
+        color
+    )
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn get_all_by_owner(pool: &PgPool, owner_id: i64) -> Result<Vec<Tag>, Error> {
+    sqlx::query_as!(
+        Tag,
+        "SELECT id, owner_id, name, color, created_at, updated_at FROM tags WHERE owner_id = $1 ORDER BY LOWER(name)",
+        owner_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_by_link(pool: &PgPool, owner_id: i64, link_id: i64) -> Result<Vec<Tag>, Error> {
+    sqlx::query_as!(
+        Tag,
+        "SELECT t.id, t.owner_id, t.name, t.color, t.created_at, t.updated_at FROM tags t JOIN link_tags lt ON lt.tag_id = t.id WHERE lt.link_id = $1 AND t.owner_id = $2 ORDER BY LOWER(t.name)",
+        link_id,
+        owner_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn update_by_owner(
+    pool: &PgPool,
+    tag_id: i64,
+    owner_id: i64,
+    name: Option<&str>,
+    color: Option<&str>,
+) -> Result<Option<Tag>, Error> {
+    sqlx::query_as!(
+        Tag,
+        r#"
+        UPDATE tags
+        SET name = COALESCE($3, name),
+            color = COALESCE($4, color),
+            updated_at = NOW()
+        WHERE id = $1 AND owner_id = $2
+        RETURNING id, owner_id, name, color, created_at, updated_at
+        "#,
+        tag_id,
+        owner_id,
+        name,
+        color
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn delete_by_owner(pool: &PgPool, tag_id: i64, owner_id: i64) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        "DELETE FROM tags WHERE id = $1 AND owner_id = $2",
+        tag_id,
+        owner_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Tags a link. Returns `false` when the link or the tag does not belong to the owner.
+pub async fn assign_to_link(pool: &PgPool, owner_id: i64, link_id: i64, tag_id: i64) -> Result<bool, Error> {
+    let found = sqlx::query_scalar!(
+        r#"
+        WITH target AS (
+            SELECT l.id AS link_id, t.id AS tag_id
+            FROM links l
+            JOIN tags t ON t.owner_id = l.owner_id
+            WHERE l.id = $1 AND t.id = $2 AND l.owner_id = $3
+        ), inserted AS (
+            INSERT INTO link_tags (link_id, tag_id)
+            SELECT link_id, tag_id FROM target
+            ON CONFLICT DO NOTHING
+        )
+        SELECT EXISTS (SELECT 1 FROM target) AS "found!"
+        "#,
+        link_id,
+        tag_id,
+        owner_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(found)
+}
+
+pub async fn remove_from_link(pool: &PgPool, owner_id: i64, link_id: i64, tag_id: i64) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        "DELETE FROM link_tags lt USING tags t WHERE lt.tag_id = t.id AND lt.link_id = $1 AND lt.tag_id = $2 AND t.owner_id = $3",
+        link_id,
+        tag_id,
+        owner_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
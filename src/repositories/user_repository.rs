@@ -1,6 +1,6 @@
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
 use crate::models::user::User;
-use crate::models::refresh_tokens::RefreshToken;
+use crate::models::refresh_tokens::{RefreshRotation, RefreshToken};
 use chrono::{DateTime, Utc};
 
 pub async fn find_by_id(pool: &PgPool, user_id: i64) -> Result<Option<User>, sqlx::Error> {
@@ -14,6 +14,31 @@ pub async fn find_by_id(pool: &PgPool, user_id: i64) -> Result<Option<User>, sql
     Ok(user)
 }
 
+pub async fn update_timezone(pool: &PgPool, user_id: i64, timezone: &str) -> Result<Option<User>, sqlx::Error> {
+    let user = sqlx::query_as!(
+        User,
+        "UPDATE users SET timezone = $2, updated_at = NOW() WHERE id = $1 RETURNING *",
+        user_id,
+        timezone
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(user)
+}
+
+/// Whether Postgres knows `name` as a timezone (IANA names such as `Europe/Berlin`).
+pub async fn timezone_exists(pool: &PgPool, name: &str) -> Result<bool, sqlx::Error> {
+    let exists = sqlx::query_scalar!(
+        "SELECT EXISTS (SELECT 1 FROM pg_timezone_names WHERE name = $1) AS \"exists!\"",
+        name
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(exists)
+}
+
 pub async fn register(
     pool: &PgPool, 
     username: &str, 
@@ -88,32 +113,90 @@ pub async fn save_refresh_token(
     pool: &PgPool,
     user_id: i64,
     token_hash: &str,
+    family_id: &str,
     expires_at: DateTime<Utc>,
 ) -> Result<RefreshToken, sqlx::Error> {
     let refresh_token = sqlx::query_as!(
         RefreshToken,
-        "INSERT INTO refresh_tokens (user_id, token_hash, expires_at) VALUES ($1, $2, $3) RETURNING *",
+        "INSERT INTO refresh_tokens (user_id, token_hash, family_id, expires_at) VALUES ($1, $2, $3, $4) RETURNING *",
         user_id,
         token_hash,
+        family_id,
         expires_at
     )
     .fetch_one(pool)
     .await?;
 
     Ok(refresh_token)
-}   
+}
 
+/// Swaps the token with hash `old_hash` for a new one in the same family, in one
+/// transaction. A token that was already revoked is treated as stolen: every
+/// token of its family is revoked instead.
+pub async fn rotate_refresh_token(
+    pool: &PgPool,
+    user_id: i64,
+    old_hash: &str,
+    new_hash: &str,
+    new_expires_at: DateTime<Utc>,
+) -> Result<RefreshRotation, sqlx::Error> {
+    let mut tx: Transaction<'_, Postgres> = pool.begin().await?;
 
-pub async fn is_refresh_token_active(pool: &PgPool, token_hash: &str) -> Result<bool, sqlx::Error> {
-   let token = sqlx::query_as!(
+    let current = sqlx::query_as!(
         RefreshToken,
-        "SELECT * FROM refresh_tokens WHERE token_hash = $1",
-        token_hash
+        "SELECT * FROM refresh_tokens WHERE token_hash = $1 FOR UPDATE",
+        old_hash
     )
-    .fetch_optional(pool)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let current = match current {
+        Some(token) if token.user_id == user_id => token,
+        _ => return Ok(RefreshRotation::Invalid),
+    };
+
+    if current.revoked_at.is_some() {
+        let result = sqlx::query!(
+            "UPDATE refresh_tokens SET revoked_at = $1 WHERE family_id = $2 AND revoked_at IS NULL",
+            Utc::now(),
+            current.family_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        return Ok(RefreshRotation::Reused {
+            user_id: current.user_id,
+            family_id: current.family_id,
+            revoked: result.rows_affected(),
+        });
+    }
+
+    if current.expires_at <= Utc::now() {
+        return Ok(RefreshRotation::Invalid);
+    }
+
+    let replacement_id = sqlx::query_scalar!(
+        "INSERT INTO refresh_tokens (user_id, token_hash, family_id, expires_at) VALUES ($1, $2, $3, $4) RETURNING id",
+        current.user_id,
+        new_hash,
+        current.family_id,
+        new_expires_at
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE refresh_tokens SET revoked_at = $1, replaced_by_id = $2 WHERE id = $3",
+        Utc::now(),
+        replacement_id,
+        current.id
+    )
+    .execute(&mut *tx)
     .await?;
 
-    Ok(token.map_or(false, |t| t.revoked_at.is_none() && t.expires_at > Utc::now()))
+    tx.commit().await?;
+    Ok(RefreshRotation::Rotated)
 }
 
 pub async fn revoke_refresh_token(pool: &PgPool, token_hash: &str) -> Result<bool, sqlx::Error> {
@@ -1,7 +1,7 @@
 use axum::{
     Router,
     middleware,
-    routing::{delete, get},
+    routing::{delete, get, post},
 };
 
 use crate::handlers::admin_handler;
@@ -18,5 +18,7 @@ pub fn routes() -> Router<AppState> {
         .route("/admin/users/{id}/hard", delete(admin_handler::hard_delete_user))
         .route("/admin/links", get(admin_handler::list_links))
         .route("/admin/links/{id}", delete(admin_handler::disable_link))
+        .route("/admin/links/{id}/restore", post(admin_handler::restore_link))
+        .route("/admin/analytics/retention", get(admin_handler::preview_retention))
         .route_layer(middleware::from_fn(admin_middleware))
 }
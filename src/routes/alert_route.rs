@@ -0,0 +1,22 @@
+use axum::{
+    Router,
+    middleware,
+    routing::{delete, get, post},
+};
+use crate::handlers::alert_handler;
+use crate::middleware::auth_middleware::auth_middleware;
+use crate::state::AppState;
+
+pub fn routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/alerts", get(alert_handler::list_alerts))
+        .route("/alerts/{id}", delete(alert_handler::delete_alert))
+        .route(
+            "/links/{id}/alerts",
+            get(alert_handler::list_link_alerts).post(alert_handler::create_link_alert),
+        )
+        .route("/notifications", get(alert_handler::list_notifications))
+        .route("/notifications/read-all", post(alert_handler::mark_all_notifications_read))
+        .route("/notifications/{id}/read", post(alert_handler::mark_notification_read))
+        .route_layer(middleware::from_fn_with_state(state, auth_middleware))
+}
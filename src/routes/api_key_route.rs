@@ -0,0 +1,15 @@
+use axum::{
+    Router,
+    middleware,
+    routing::{delete, get},
+};
+use crate::handlers::api_key_handler;
+use crate::middleware::auth_middleware::auth_middleware;
+use crate::state::AppState;
+
+pub fn routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/api-keys", get(api_key_handler::list_api_keys).post(api_key_handler::create_api_key))
+        .route("/api-keys/{id}", delete(api_key_handler::revoke_api_key))
+        .route_layer(middleware::from_fn_with_state(state, auth_middleware))
+}
@@ -3,20 +3,30 @@ use axum::{
     middleware,
     routing::{get, post, delete},
 };
-use crate::handlers::link_handler;
+use crate::handlers::{export_handler, link_handler, live_handler};
 use crate::middleware::auth_middleware::auth_middleware;
 use crate::state::AppState;
 
-pub fn routes() -> Router<AppState> {
+pub fn routes(state: AppState) -> Router<AppState> {
     let public_routes = Router::new()
-        .route("/{short_code}", get(link_handler::redirect_link));
+        .route("/{short_code}", get(link_handler::redirect_link).post(link_handler::unlock_link));
 
     let protected_routes = Router::new()
         .route("/links", post(link_handler::create_link))
+        .route("/links/bulk", post(link_handler::create_links_bulk))
         .route("/links/analytics", get(link_handler::get_daily_analytics))
+        .route("/links/analytics/export", get(export_handler::export_click_series))
+        .route("/links/analytics/{dimension}", get(link_handler::get_breakdown))
         .route("/links/my-links", get(link_handler::get_my_links))
-        .route("/links/{id}", delete(link_handler::delete_link))
-        .route_layer(middleware::from_fn(auth_middleware));
+        .route("/links/export", get(export_handler::export_links))
+        .route("/links/live", get(live_handler::live_clicks))
+        .route("/links/trash", get(link_handler::get_trash))
+        .route("/links/{id}", delete(link_handler::delete_link).patch(link_handler::update_link))
+        .route("/links/{id}/restore", post(link_handler::restore_link))
+        .route("/links/{id}/analytics", get(link_handler::get_link_analytics))
+        .route("/links/{id}/live", get(live_handler::live_link_clicks))
+        .route("/links/{id}/analytics/{dimension}", get(link_handler::get_link_breakdown))
+        .route_layer(middleware::from_fn_with_state(state, auth_middleware));
 
     public_routes.merge(protected_routes)
 }
@@ -2,3 +2,6 @@ pub mod user_route;
 pub mod link_route;
 pub mod health_route;
 pub mod admin_route;
+pub mod tag_route;
+pub mod alert_route;
+pub mod api_key_route;
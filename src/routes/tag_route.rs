@@ -0,0 +1,21 @@
+use axum::{
+    Router,
+    middleware,
+    routing::{get, patch, put},
+};
+use crate::handlers::tag_handler;
+use crate::middleware::auth_middleware::auth_middleware;
+use crate::state::AppState;
+
+pub fn routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/tags", get(tag_handler::list_tags).post(tag_handler::create_tag))
+        .route("/tags/analytics", get(tag_handler::get_tag_analytics))
+        .route("/tags/{id}", patch(tag_handler::update_tag).delete(tag_handler::delete_tag))
+        .route("/links/{id}/tags", get(tag_handler::get_link_tags))
+        .route(
+            "/links/{id}/tags/{tag_id}",
+            put(tag_handler::add_tag_to_link).delete(tag_handler::remove_tag_from_link),
+        )
+        .route_layer(middleware::from_fn_with_state(state, auth_middleware))
+}
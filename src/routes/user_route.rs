@@ -7,7 +7,7 @@ use crate::handlers::user_handler;
 use crate::middleware::auth_middleware::auth_middleware;
 use crate::state::AppState;
 
-pub fn routes() -> Router<AppState> {
+pub fn routes(state: AppState) -> Router<AppState> {
     let public_routes = Router::new()
         .route("/register", post(user_handler::register_user))
         .route("/login", post(user_handler::login_user))
@@ -15,8 +15,8 @@ pub fn routes() -> Router<AppState> {
         .route("/logout", post(user_handler::logout_user));
 
     let protected_routes = Router::new()
-        .route("/users/me", get(user_handler::get_me))
-        .route_layer(middleware::from_fn(auth_middleware));
+        .route("/users/me", get(user_handler::get_me).patch(user_handler::update_me))
+        .route_layer(middleware::from_fn_with_state(state, auth_middleware));
 
     public_routes.merge(protected_routes)
 }
\ No newline at end of file
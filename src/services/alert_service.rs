@@ -0,0 +1,159 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{PgPool, Error};
+use tokio::task::JoinHandle;
+
+use crate::models::alert::{AlertRule, FiredAlert, NewAlertRule, Notification};
+use crate::repositories::{alert_repository, link_repository};
+use crate::utils::webhook;
+
+const DEFAULT_ALERT_INTERVAL_SECS: u64 = 60;
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Creates a rule on one of the user's links; `None` when the link is not theirs.
+pub async fn create_alert(pool: &PgPool, user_id: i64, rule: &NewAlertRule) -> Result<Option<AlertRule>, Error> {
+    if link_repository::find_by_id_and_owner(pool, rule.link_id, user_id).await?.is_none() {
+        return Ok(None);
+    }
+    alert_repository::create(pool, user_id, rule).await.map(Some)
+}
+
+pub async fn list_alerts(pool: &PgPool, user_id: i64) -> Result<Vec<AlertRule>, Error> {
+    alert_repository::get_by_owner(pool, user_id, None).await
+}
+
+/// Rules of one of the user's links; `None` when the link is not theirs.
+pub async fn list_link_alerts(pool: &PgPool, user_id: i64, link_id: i64) -> Result<Option<Vec<AlertRule>>, Error> {
+    if link_repository::find_by_id_and_owner(pool, link_id, user_id).await?.is_none() {
+        return Ok(None);
+    }
+    alert_repository::get_by_owner(pool, user_id, Some(link_id)).await.map(Some)
+}
+
+pub async fn delete_alert(pool: &PgPool, user_id: i64, rule_id: i64) -> Result<bool, Error> {
+    alert_repository::delete_by_owner(pool, rule_id, user_id).await
+}
+
+pub async fn list_notifications(
+    pool: &PgPool,
+    user_id: i64,
+    unread_only: bool,
+    before_id: Option<i64>,
+    limit: i64,
+) -> Result<(Vec<Notification>, i64), Error> {
+    let items = alert_repository::get_notifications(pool, user_id, unread_only, before_id, limit).await?;
+    let unread = alert_repository::count_unread(pool, user_id).await?;
+    Ok((items, unread))
+}
+
+pub async fn mark_notification_read(pool: &PgPool, user_id: i64, notification_id: i64) -> Result<Option<Notification>, Error> {
+    alert_repository::mark_read(pool, notification_id, user_id).await
+}
+
+pub async fn mark_all_notifications_read(pool: &PgPool, user_id: i64) -> Result<u64, Error> {
+    alert_repository::mark_all_read(pool, user_id).await
+}
+
+/// Body POSTed to a rule's webhook when it fires.
+#[derive(Debug, Serialize)]
+struct AlertWebhookPayload {
+    event: &'static str,
+    notification_id: i64,
+    alert_rule_id: i64,
+    link_id: i64,
+    short_code: String,
+    kind: String,
+    message: String,
+    clicks: i64,
+    created_at: DateTime<Utc>,
+}
+
+/// Fires every rule whose condition holds, writes the notifications and hands
+/// the ones with a webhook to `client`. Returns how many rules fired.
+pub async fn evaluate_alerts(pool: &PgPool, client: &reqwest::Client) -> Result<usize, Error> {
+    let mut fired = alert_repository::fire_threshold_alerts(pool).await?;
+    fired.extend(alert_repository::fire_spike_alerts(pool).await?);
+
+    let count = fired.len();
+    for alert in fired {
+        if let Some(url) = alert.webhook_url.clone() {
+            let client = client.clone();
+            let body = AlertWebhookPayload::from(alert);
+            tokio::spawn(async move {
+                if let Err(e) = send_webhook(&client, &url, &body).await {
+                    tracing::warn!("Alert webhook {} failed: {:?}", url, e);
+                }
+            });
+        }
+    }
+
+    Ok(count)
+}
+
+impl From<FiredAlert> for AlertWebhookPayload {
+    fn from(alert: FiredAlert) -> Self {
+        Self {
+            event: "alert",
+            notification_id: alert.notification_id,
+            alert_rule_id: alert.alert_rule_id,
+            link_id: alert.link_id,
+            short_code: alert.short_code,
+            kind: alert.kind,
+            message: alert.message,
+            clicks: alert.clicks,
+            created_at: alert.created_at,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+enum WebhookError {
+    #[error("Webhook target rejected: {0}")]
+    Target(String),
+
+    #[error("Webhook request failed: {0}")]
+    Request(#[from] reqwest::Error),
+}
+
+/// One attempt, no retries: the notification is already in the inbox. The
+/// target is checked again since its DNS may have changed since the rule was saved.
+async fn send_webhook(client: &reqwest::Client, url: &str, body: &AlertWebhookPayload) -> Result<(), WebhookError> {
+    webhook::check_target(url).await.map_err(WebhookError::Target)?;
+    client
+        .post(url)
+        .timeout(WEBHOOK_TIMEOUT)
+        .json(body)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+/// Evaluation interval from `ALERT_EVAL_INTERVAL_SECS` (default 60 seconds).
+pub fn alert_interval_from_env() -> Duration {
+    let secs = std::env::var("ALERT_EVAL_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|&v| v > 0)
+        .unwrap_or(DEFAULT_ALERT_INTERVAL_SECS);
+    Duration::from_secs(secs)
+}
+
+/// Runs `evaluate_alerts` on a fixed interval until the task is aborted.
+pub fn spawn_alert_evaluator(pool: PgPool, interval: Duration) -> JoinHandle<()> {
+    let client = webhook::client();
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match evaluate_alerts(&pool, &client).await {
+                Ok(0) => {}
+                Ok(fired) => tracing::info!("Alerts fired: {}", fired),
+                Err(e) => tracing::warn!("Alert evaluation failed: {:?}", e),
+            }
+        }
+    })
+}
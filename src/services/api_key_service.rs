@@ -0,0 +1,83 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::{PgPool, Error};
+use uuid::Uuid;
+
+use crate::dtos::claims::Claims;
+use crate::models::api_key::ApiKey;
+use crate::repositories::api_key_repository;
+
+/// Every key starts with this, so a key passed as a Bearer token can be told apart from a JWT.
+pub const API_KEY_PREFIX: &str = "usk_";
+/// Characters of the key kept in clear to identify it in listings.
+const DISPLAY_PREFIX_LEN: usize = 12;
+/// `last_used_at` is written at most this often per key.
+const LAST_USED_RESOLUTION: TimeDelta = TimeDelta::minutes(1);
+
+fn hash_api_key(key: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(key.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// A new random key: the prefix and 256 bits of hex.
+fn generate_api_key() -> String {
+    format!("{}{}{}", API_KEY_PREFIX, Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// Creates a key and returns it with its plaintext, which is never stored.
+pub async fn create_api_key(
+    pool: &PgPool,
+    user_id: i64,
+    name: &str,
+    scopes: &[String],
+    expires_at: Option<DateTime<Utc>>,
+) -> Result<(ApiKey, String), Error> {
+    let key = generate_api_key();
+    let api_key = api_key_repository::create(
+        pool,
+        user_id,
+        name.trim(),
+        &key[..DISPLAY_PREFIX_LEN],
+        &hash_api_key(&key),
+        scopes,
+        expires_at,
+    )
+    .await?;
+    Ok((api_key, key))
+}
+
+pub async fn list_api_keys(pool: &PgPool, user_id: i64) -> Result<Vec<ApiKey>, Error> {
+    api_key_repository::get_by_user(pool, user_id).await
+}
+
+pub async fn revoke_api_key(pool: &PgPool, user_id: i64, key_id: i64) -> Result<Option<ApiKey>, Error> {
+    api_key_repository::revoke_by_user(pool, key_id, user_id).await
+}
+
+/// Claims for a request made with `key`, or `None` when the key is unknown,
+/// revoked, expired or belongs to a disabled user.
+pub async fn authenticate(pool: &PgPool, key: &str) -> Result<Option<Claims>, Error> {
+    let Some(api_key) = api_key_repository::find_active_by_hash(pool, &hash_api_key(key)).await? else {
+        return Ok(None);
+    };
+
+    let now = Utc::now();
+    if api_key.last_used_at.is_none_or(|used| now - used >= LAST_USED_RESOLUTION) {
+        let pool = pool.clone();
+        tokio::spawn(async move {
+            if let Err(e) = api_key_repository::touch_last_used(&pool, api_key.id).await {
+                tracing::warn!("Failed to record API key use: {:?}", e);
+            }
+        });
+    }
+
+    Ok(Some(Claims {
+        sub: api_key.user_id.to_string(),
+        role: api_key.role,
+        iat: api_key.created_at.timestamp() as usize,
+        exp: api_key.expires_at.map_or(usize::MAX, |at| at.timestamp() as usize),
+        jti: Some(format!("api_key:{}", api_key.id)),
+        scopes: Some(api_key.scopes),
+    }))
+}
@@ -1,4 +1,6 @@
+use chrono::{DateTime, Utc};
 use deadpool_redis::redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, thiserror::Error)]
 pub enum CacheError {
@@ -7,29 +9,60 @@ pub enum CacheError {
 
     #[error("Redis error: {0}")]
     Redis(#[from] deadpool_redis::redis::RedisError),
+
+    #[error("Cache serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
 }
 
 const URL_CACHE_PREFIX: &str = "url:";
 const CACHE_TTL_SECONDS: u64 = 3600;
 
-pub async fn get_cached_url(
+/// What a cache hit needs to answer a redirect exactly like the database path would.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedLink {
+    /// Needed to count clicks served from the cache.
+    pub link_id: i64,
+    /// Owner whose live click stream hears about the click; missing in entries from older builds.
+    #[serde(default)]
+    pub owner_id: Option<i64>,
+    pub url: String,
+    pub status: i16,
+    pub max_age: Option<i32>,
+}
+
+/// Entries that do not parse (e.g. plain URLs written by an older build) are treated as a miss.
+pub async fn get_cached_link(
     redis: &deadpool_redis::Pool,
     short_code: &str,
-) -> Result<Option<String>, CacheError> {
+) -> Result<Option<CachedLink>, CacheError> {
     let mut conn = redis.get().await?;
     let cache_key = format!("{}{}", URL_CACHE_PREFIX, short_code);
     let result: Option<String> = conn.get(&cache_key).await?;
-    Ok(result)
+    Ok(result.and_then(|raw| serde_json::from_str(&raw).ok()))
 }
 
-pub async fn set_cached_url(
+/// Caches a link, never past the link's own expiry so a cache hit cannot outlive the link.
+pub async fn set_cached_link(
     redis: &deadpool_redis::Pool,
     short_code: &str,
-    original_url: &str,
+    cached: &CachedLink,
+    expires_at: Option<DateTime<Utc>>,
 ) -> Result<(), CacheError> {
+    let ttl = match expires_at {
+        Some(expires_at) => {
+            let remaining = (expires_at - Utc::now()).num_seconds();
+            if remaining <= 0 {
+                return Ok(());
+            }
+            CACHE_TTL_SECONDS.min(remaining as u64)
+        }
+        None => CACHE_TTL_SECONDS,
+    };
+
     let mut conn = redis.get().await?;
     let cache_key = format!("{}{}", URL_CACHE_PREFIX, short_code);
-    conn.set_ex::<_, _, ()>(&cache_key, original_url, CACHE_TTL_SECONDS).await?;
+    let value = serde_json::to_string(cached)?;
+    conn.set_ex::<_, _, ()>(&cache_key, value, ttl).await?;
     Ok(())
 }
 
@@ -0,0 +1,121 @@
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use axum::http::{HeaderMap, Method};
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::models::click_event::NewClickEvent;
+use crate::repositories::click_event_repository;
+use crate::utils::{bot_detection, client_info};
+
+const QUEUE_CAPACITY: usize = 10_000;
+const MAX_BATCH_SIZE: usize = 500;
+
+/// What the redirect path knows about the visitor behind a click.
+#[derive(Debug, Clone, Default)]
+pub struct ClickContext {
+    pub ip: Option<IpAddr>,
+    pub referrer_host: Option<String>,
+    pub user_agent: Option<String>,
+    pub country: Option<String>,
+    /// Crawler, unfurler, monitor or prefetch rather than a person; see `bot_detection`.
+    pub is_bot: bool,
+}
+
+impl ClickContext {
+    pub fn from_request(method: &Method, headers: &HeaderMap, peer: Option<SocketAddr>) -> Self {
+        let user_agent = client_info::user_agent(headers);
+        Self {
+            ip: client_info::client_ip(headers, peer),
+            referrer_host: client_info::referrer_host(headers),
+            is_bot: bot_detection::is_bot_request(method, headers, user_agent.as_deref()),
+            user_agent,
+            country: client_info::country(headers),
+        }
+    }
+}
+
+/// Queues click events and writes them to `click_events` in batches from a
+/// background task, so the redirect never waits on the insert.
+#[derive(Clone)]
+pub struct ClickEventWriter {
+    sender: mpsc::Sender<NewClickEvent>,
+    ip_salt: Arc<str>,
+}
+
+impl ClickEventWriter {
+    /// Starts the writer task. It drains the queue and exits once every
+    /// `ClickEventWriter` clone has been dropped.
+    pub fn spawn(pool: PgPool) -> (Self, JoinHandle<()>) {
+        let (sender, mut receiver) = mpsc::channel::<NewClickEvent>(QUEUE_CAPACITY);
+
+        let handle = tokio::spawn(async move {
+            let mut batch = Vec::with_capacity(MAX_BATCH_SIZE);
+            while receiver.recv_many(&mut batch, MAX_BATCH_SIZE).await > 0 {
+                if let Err(e) = click_event_repository::insert_many(&pool, &batch).await {
+                    tracing::warn!("Failed to write {} click events: {:?}", batch.len(), e);
+                }
+                batch.clear();
+            }
+        });
+
+        let writer = Self {
+            sender,
+            ip_salt: ip_salt_from_env().into(),
+        };
+        (writer, handle)
+    }
+
+    /// Queues one click. Drops the event when the queue is full rather than
+    /// slowing the redirect down.
+    pub fn record(&self, link_id: i64, context: &ClickContext) {
+        let event = NewClickEvent {
+            link_id,
+            clicked_at: Utc::now(),
+            referrer_host: context.referrer_host.clone(),
+            user_agent: context.user_agent.clone(),
+            country: context.country.clone(),
+            ip_hash: context.ip.map(|ip| self.hash_ip(ip)),
+            is_bot: context.is_bot,
+        };
+
+        if let Err(e) = self.sender.try_send(event) {
+            tracing::warn!("Click event dropped: {}", e);
+        }
+    }
+
+    /// Salted SHA-256 of the address, so raw IPs are never stored.
+    pub fn hash_ip(&self, ip: IpAddr) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.ip_salt.as_bytes());
+        hasher.update(ip.to_string().as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Identifies a visitor for unique counts: the salted IP hash combined with
+    /// the user agent. `None` when the client address is unknown.
+    pub fn visitor_fingerprint(&self, context: &ClickContext) -> Option<String> {
+        let ip_hash = self.hash_ip(context.ip?);
+        let mut hasher = Sha256::new();
+        hasher.update(ip_hash.as_bytes());
+        hasher.update(b"\n");
+        hasher.update(context.user_agent.as_deref().unwrap_or_default().as_bytes());
+        Some(format!("{:x}", hasher.finalize()))
+    }
+}
+
+/// `CLICK_IP_SALT` keeps hashes comparable across restarts. Without it a random
+/// salt is used, so the same visitor hashes differently after every restart.
+fn ip_salt_from_env() -> String {
+    match std::env::var("CLICK_IP_SALT") {
+        Ok(salt) if !salt.is_empty() => salt,
+        _ => {
+            tracing::warn!("CLICK_IP_SALT not set, using a random salt for this process");
+            uuid::Uuid::new_v4().to_string()
+        }
+    }
+}
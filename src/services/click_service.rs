@@ -0,0 +1,374 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use chrono::{DateTime, DurationRound, NaiveDate, NaiveDateTime, TimeDelta, Utc};
+use deadpool_redis::redis::{self, AsyncCommands};
+use sqlx::PgPool;
+use tokio::task::JoinHandle;
+
+use crate::models::link_analytics::{ClickDelta, DailyVisitorSketch, PeriodVisitorSketch, VisitorSketch};
+use crate::repositories::link_repository;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClickFlushError {
+    #[error("Redis pool error: {0}")]
+    Pool(#[from] deadpool_redis::PoolError),
+
+    #[error("Redis error: {0}")]
+    Redis(#[from] deadpool_redis::redis::RedisError),
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// Hash of `{link_id}:{unix seconds of the UTC hour}` -> clicks not yet written to Postgres.
+/// Bot hits use the same field with a `:bot` suffix, and a `:visitors` suffix
+/// marks an hour whose visitor sketch changed and must be copied to Postgres.
+const PENDING_CLICKS_KEY: &str = "clicks:pending";
+/// A flush renames the pending hash to `clicks:inflight:{flush_id}` before applying it.
+const INFLIGHT_CLICKS_PREFIX: &str = "clicks:inflight:";
+const BOT_FIELD_SUFFIX: &str = ":bot";
+const VISITORS_FIELD_SUFFIX: &str = ":visitors";
+/// HyperLogLog of visitor fingerprints: `visitors:{link_id}:{unix seconds of the UTC hour}`.
+const VISITOR_SKETCH_PREFIX: &str = "visitors:";
+/// Union of a link's hourly sketches over a UTC day: `visitors:day:{link_id}:{YYYY-MM-DD}`.
+const VISITOR_DAY_PREFIX: &str = "visitors:day:";
+/// Redis only has to hold a sketch until its hour has been flushed; Postgres keeps the history.
+const VISITOR_SKETCH_TTL_SECS: i64 = 2 * 24 * 60 * 60;
+/// Link days merged per pass when building daily unions for older hourly sketches.
+const VISITOR_DAY_BACKFILL_BATCH: i64 = 200;
+/// Scratch keys used to load stored sketches back into Redis for counting.
+const VISITOR_SCRATCH_TTL_SECS: u64 = 60;
+const DEFAULT_FLUSH_INTERVAL_SECS: u64 = 5;
+
+/// Counts a click in the background. Used for links without a click budget and
+/// for bot hits on any link, whichever tier (database or Redis cache) served the
+/// redirect. The click is buffered in Redis; if Redis is unavailable it is
+/// written to Postgres directly.
+pub fn record_click(pool: &PgPool, redis: &deadpool_redis::Pool, link_id: i64, is_bot: bool) {
+    let pool = pool.clone();
+    let redis = redis.clone();
+    let hour = current_hour();
+    tokio::spawn(async move {
+        if let Err(e) = buffer_click(&redis, link_id, hour, is_bot).await {
+            tracing::warn!("Click buffer write failed, writing to database: {:?}", e);
+            let result = if is_bot {
+                link_repository::increment_bot_clicks(&pool, link_id, hour).await
+            } else {
+                link_repository::increment_click_and_analytics(&pool, link_id, hour).await.map(|_| ())
+            };
+            if let Err(e) = result {
+                tracing::warn!("Async analytics update failed: {:?}", e);
+            }
+        }
+    });
+}
+
+/// Adds a visitor fingerprint to the link's sketch for the current hour, in the
+/// background. Only human clicks are passed in, so bots never count as visitors.
+pub fn record_visitor(redis: &deadpool_redis::Pool, link_id: i64, fingerprint: String) {
+    let redis = redis.clone();
+    let hour = current_hour();
+    tokio::spawn(async move {
+        if let Err(e) = add_visitor(&redis, link_id, hour, &fingerprint).await {
+            tracing::warn!("Visitor sketch update failed: {:?}", e);
+        }
+    });
+}
+
+async fn add_visitor(
+    redis: &deadpool_redis::Pool,
+    link_id: i64,
+    hour: DateTime<Utc>,
+    fingerprint: &str,
+) -> Result<(), ClickFlushError> {
+    let mut conn = redis.get().await?;
+    let key = format!("{}{}:{}", VISITOR_SKETCH_PREFIX, link_id, hour.timestamp());
+    let field = format!("{}:{}{}", link_id, hour.timestamp(), VISITORS_FIELD_SUFFIX);
+    redis::pipe()
+        .pfadd(&key, fingerprint)
+        .ignore()
+        .expire(&key, VISITOR_SKETCH_TTL_SECS)
+        .ignore()
+        .hincr(PENDING_CLICKS_KEY, field, 1)
+        .ignore()
+        .query_async::<_, ()>(&mut conn)
+        .await?;
+    Ok(())
+}
+
+/// Approximate unique visitors per bucket: the union of each bucket's hourly or
+/// daily sketches, counted by loading them into scratch keys and running `PFCOUNT` over them.
+pub async fn count_uniques(
+    redis: &deadpool_redis::Pool,
+    sketches: Vec<PeriodVisitorSketch>,
+) -> Result<HashMap<NaiveDateTime, i64>, ClickFlushError> {
+    let mut by_period: HashMap<NaiveDateTime, Vec<Vec<u8>>> = HashMap::new();
+    for sketch in sketches {
+        by_period.entry(sketch.period).or_default().push(sketch.visitors);
+    }
+    if by_period.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let scratch_prefix = format!("{}scratch:{}:", VISITOR_SKETCH_PREFIX, uuid::Uuid::new_v4());
+    let mut pipe = redis::pipe();
+    let mut periods = Vec::with_capacity(by_period.len());
+    let mut scratch_keys = Vec::new();
+    for (period, period_sketches) in by_period {
+        let period_keys: Vec<String> = (0..period_sketches.len())
+            .map(|i| format!("{}{}:{}", scratch_prefix, period.and_utc().timestamp(), i))
+            .collect();
+        for (key, visitors) in period_keys.iter().zip(period_sketches) {
+            pipe.set_ex(key, visitors, VISITOR_SCRATCH_TTL_SECS).ignore();
+        }
+        pipe.pfcount(&period_keys);
+        periods.push(period);
+        scratch_keys.extend(period_keys);
+    }
+    pipe.del(&scratch_keys).ignore();
+
+    let mut conn = redis.get().await?;
+    let counts: Vec<i64> = pipe.query_async(&mut conn).await?;
+    Ok(periods.into_iter().zip(counts).collect())
+}
+
+/// Start of the current UTC hour, the bucket a click made now is counted in.
+pub fn current_hour() -> DateTime<Utc> {
+    let now = Utc::now();
+    now.duration_trunc(TimeDelta::hours(1)).unwrap_or(now)
+}
+
+async fn buffer_click(
+    redis: &deadpool_redis::Pool,
+    link_id: i64,
+    hour: DateTime<Utc>,
+    is_bot: bool,
+) -> Result<(), ClickFlushError> {
+    let mut conn = redis.get().await?;
+    let suffix = if is_bot { BOT_FIELD_SUFFIX } else { "" };
+    let field = format!("{}:{}{}", link_id, hour.timestamp(), suffix);
+    conn.hincr::<_, _, _, ()>(PENDING_CLICKS_KEY, field, 1).await?;
+    Ok(())
+}
+
+/// Moves buffered clicks into Postgres and returns how many were applied.
+///
+/// Batches left behind by an interrupted flush are retried first. Each batch
+/// keeps its Redis key until Postgres has committed it, and Postgres remembers
+/// the batch id, so a retry after a crash never counts a click twice.
+pub async fn flush_pending_clicks(pool: &PgPool, redis: &deadpool_redis::Pool) -> Result<i64, ClickFlushError> {
+    let mut conn = redis.get().await?;
+
+    let mut batch_keys: Vec<String> = {
+        let mut iter = conn
+            .scan_match::<_, String>(format!("{}*", INFLIGHT_CLICKS_PREFIX))
+            .await?;
+        let mut keys = Vec::new();
+        while let Some(key) = iter.next_item().await {
+            keys.push(key);
+        }
+        keys
+    };
+
+    if conn.exists::<_, bool>(PENDING_CLICKS_KEY).await? {
+        let batch_key = format!("{}{}", INFLIGHT_CLICKS_PREFIX, uuid::Uuid::new_v4());
+        conn.rename::<_, _, ()>(PENDING_CLICKS_KEY, &batch_key).await?;
+        batch_keys.push(batch_key);
+    }
+
+    let mut applied = 0;
+    for batch_key in batch_keys {
+        let entries: HashMap<String, i64> = conn.hgetall(&batch_key).await?;
+        let (deltas, visitor_hours) = parse_click_deltas(&entries);
+        let flush_id = &batch_key[INFLIGHT_CLICKS_PREFIX.len()..];
+
+        if deltas.is_empty() || link_repository::apply_click_deltas(pool, flush_id, &deltas).await? {
+            applied += deltas.iter().map(|d| i64::from(d.clicks) + i64::from(d.bot_clicks)).sum::<i64>();
+        }
+        persist_visitor_sketches(pool, &mut conn, &visitor_hours).await?;
+        conn.del::<_, ()>(&batch_key).await?;
+    }
+
+    Ok(applied)
+}
+
+/// Copies the current Redis sketch of each hour that saw new visitors to Postgres,
+/// then merges those hours into their day's union and stores that too.
+async fn persist_visitor_sketches(
+    pool: &PgPool,
+    conn: &mut deadpool_redis::Connection,
+    visitor_hours: &HashSet<(i64, DateTime<Utc>)>,
+) -> Result<(), ClickFlushError> {
+    let mut sketches = Vec::with_capacity(visitor_hours.len());
+    let mut hours_by_day: HashMap<(i64, NaiveDate), Vec<String>> = HashMap::new();
+    for &(link_id, hour) in visitor_hours {
+        let key = format!("{}{}:{}", VISITOR_SKETCH_PREFIX, link_id, hour.timestamp());
+        // An evicted sketch is skipped rather than overwriting the stored copy.
+        if let Some(visitors) = conn.get::<_, Option<Vec<u8>>>(&key).await? {
+            sketches.push(VisitorSketch { link_id, hour, visitors });
+            hours_by_day.entry((link_id, hour.date_naive())).or_default().push(key);
+        }
+    }
+    if sketches.is_empty() {
+        return Ok(());
+    }
+    link_repository::store_visitor_sketches(pool, &sketches).await?;
+
+    let mut daily = Vec::with_capacity(hours_by_day.len());
+    for ((link_id, day), hour_keys) in hours_by_day {
+        let key = format!("{}{}:{}", VISITOR_DAY_PREFIX, link_id, day);
+        if conn.exists::<_, bool>(&key).await? {
+            conn.pfmerge::<_, _, ()>(&key, &hour_keys).await?;
+        } else {
+            // Rebuilt from every stored hour of the day, which now includes these ones.
+            let stored = link_repository::get_hourly_visitor_sketches_of_day(pool, link_id, day).await?;
+            merge_sketches(conn, &key, stored).await?;
+        }
+        conn.expire::<_, ()>(&key, VISITOR_SKETCH_TTL_SECS).await?;
+        if let Some(visitors) = conn.get::<_, Option<Vec<u8>>>(&key).await? {
+            daily.push(DailyVisitorSketch { link_id, day, visitors });
+        }
+    }
+    link_repository::store_daily_visitor_sketches(pool, &daily).await?;
+    Ok(())
+}
+
+/// Unions stored sketches into `dest` through short-lived scratch keys.
+async fn merge_sketches(
+    conn: &mut deadpool_redis::Connection,
+    dest: &str,
+    sketches: Vec<Vec<u8>>,
+) -> Result<(), ClickFlushError> {
+    let scratch_prefix = format!("{}scratch:{}:", VISITOR_SKETCH_PREFIX, uuid::Uuid::new_v4());
+    let scratch_keys: Vec<String> = (0..sketches.len()).map(|i| format!("{}{}", scratch_prefix, i)).collect();
+    let mut pipe = redis::pipe();
+    for (key, visitors) in scratch_keys.iter().zip(sketches) {
+        pipe.set_ex(key, visitors, VISITOR_SCRATCH_TTL_SECS).ignore();
+    }
+    pipe.pfmerge(dest, &scratch_keys).ignore();
+    if !scratch_keys.is_empty() {
+        pipe.del(&scratch_keys).ignore();
+    }
+    pipe.query_async::<_, ()>(conn).await?;
+    Ok(())
+}
+
+/// Builds the missing daily unions of hourly sketches stored before daily
+/// unions existed. Returns how many link days were filled in.
+pub async fn backfill_daily_visitor_sketches(pool: &PgPool, redis: &deadpool_redis::Pool) -> Result<usize, ClickFlushError> {
+    let mut conn = redis.get().await?;
+    let mut filled = 0;
+    loop {
+        let days = link_repository::find_visitor_days_without_union(pool, VISITOR_DAY_BACKFILL_BATCH).await?;
+        if days.is_empty() {
+            return Ok(filled);
+        }
+
+        let mut daily = Vec::with_capacity(days.len());
+        for day in &days {
+            let stored = link_repository::get_hourly_visitor_sketches_of_day(pool, day.link_id, day.day).await?;
+            let key = format!("{}scratch:{}", VISITOR_SKETCH_PREFIX, uuid::Uuid::new_v4());
+            merge_sketches(&mut conn, &key, stored).await?;
+            let visitors: Option<Vec<u8>> = redis::pipe()
+                .get(&key)
+                .del(&key)
+                .ignore()
+                .query_async::<_, (Option<Vec<u8>>,)>(&mut conn)
+                .await?
+                .0;
+            if let Some(visitors) = visitors {
+                daily.push(DailyVisitorSketch { link_id: day.link_id, day: day.day, visitors });
+            }
+        }
+        link_repository::store_daily_visitor_sketches(pool, &daily).await?;
+        filled += daily.len();
+
+        if (days.len() as i64) < VISITOR_DAY_BACKFILL_BATCH || daily.len() < days.len() {
+            return Ok(filled);
+        }
+    }
+}
+
+/// Splits a buffered batch into click deltas and the hours whose visitor sketch changed.
+fn parse_click_deltas(entries: &HashMap<String, i64>) -> (Vec<ClickDelta>, HashSet<(i64, DateTime<Utc>)>) {
+    let mut deltas: HashMap<(i64, DateTime<Utc>), ClickDelta> = HashMap::new();
+    let mut visitor_hours = HashSet::new();
+
+    for (field, &count) in entries {
+        let (field, is_visitors) = match field.strip_suffix(VISITORS_FIELD_SUFFIX) {
+            Some(field) => (field, true),
+            None => (field.as_str(), false),
+        };
+        let (field, is_bot) = match field.strip_suffix(BOT_FIELD_SUFFIX) {
+            Some(field) => (field, true),
+            None => (field, false),
+        };
+        let Some((link_id, bucket)) = field.split_once(':') else {
+            continue;
+        };
+        let (Ok(link_id), Some(hour), Ok(count)) = (link_id.parse::<i64>(), parse_click_bucket(bucket), i32::try_from(count)) else {
+            continue;
+        };
+        if count <= 0 {
+            continue;
+        }
+        if is_visitors {
+            visitor_hours.insert((link_id, hour));
+            continue;
+        }
+
+        let delta = deltas.entry((link_id, hour)).or_insert(ClickDelta {
+            link_id,
+            hour,
+            clicks: 0,
+            bot_clicks: 0,
+        });
+        if is_bot {
+            delta.bot_clicks += count;
+        } else {
+            delta.clicks += count;
+        }
+    }
+
+    (deltas.into_values().collect(), visitor_hours)
+}
+
+fn parse_click_bucket(bucket: &str) -> Option<DateTime<Utc>> {
+    if let Ok(secs) = bucket.parse::<i64>() {
+        return DateTime::from_timestamp(secs, 0);
+    }
+    // Buffers written before hourly buckets keyed clicks by their UTC+7 day.
+    let date = NaiveDate::parse_from_str(bucket, "%Y-%m-%d").ok()?;
+    Some(date.and_hms_opt(0, 0, 0)?.and_utc() - TimeDelta::hours(7))
+}
+
+/// Flush interval from `CLICK_FLUSH_INTERVAL_SECS` (default 5 seconds).
+pub fn flush_interval_from_env() -> Duration {
+    let secs = std::env::var("CLICK_FLUSH_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|&v| v > 0)
+        .unwrap_or(DEFAULT_FLUSH_INTERVAL_SECS);
+    Duration::from_secs(secs)
+}
+
+/// Runs `flush_pending_clicks` on a fixed interval until the task is aborted,
+/// after filling in any missing daily visitor unions.
+pub fn spawn_click_flusher(pool: PgPool, redis: deadpool_redis::Pool, interval: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        match backfill_daily_visitor_sketches(&pool, &redis).await {
+            Ok(0) => {}
+            Ok(days) => tracing::info!("Built {} daily visitor sketches", days),
+            Err(e) => tracing::warn!("Daily visitor sketch backfill failed: {:?}", e),
+        }
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = flush_pending_clicks(&pool, &redis).await {
+                tracing::warn!("Click flush failed: {:?}", e);
+            }
+        }
+    })
+}
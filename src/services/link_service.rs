@@ -1,11 +1,25 @@
+use futures_util::stream::BoxStream;
 use sqlx::{PgPool, Error};
-use chrono::{NaiveDate, Utc, FixedOffset};
-use crate::models::link::Link;
-use crate::models::link_analytics::DailyClickTotal;
-use crate::repositories::link_repository;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::env;
+use uuid::Uuid;
+use crate::models::click_event::{BreakdownDimension, ClickEventFilter};
+use crate::models::link::{Link, LinkChanges, LinkCursor, LinkSearch, LinkSortField, NewLink};
+use crate::models::link_analytics::{AnalyticsGranularity, ClickSeriesPoint, LinkClickSeriesRow};
+use crate::db::is_unique_violation;
+use crate::repositories::{click_event_repository, link_repository};
+use crate::services::click_service;
+use crate::utils::{client_info, user_agent};
 
 const BASE62_CHARSET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
 
+/// Width of obfuscated codes; ids are permuted inside the 62^8 code space.
+const OBFUSCATED_WIDTH: u32 = 8;
+/// About 62^8 / golden ratio; odd and not a multiple of 31, so it is invertible modulo 62^8.
+const OBFUSCATION_MULTIPLIER: u128 = 134_941_606_347_813;
+const OBFUSCATION_ROUNDS: usize = 2;
+
 fn encode_base62(mut num: i64) -> String {
     if num == 0 {
         return "0".to_string();
@@ -23,88 +37,805 @@ fn encode_base62(mut num: i64) -> String {
     buf.iter().rev().collect()
 }
 
+/// How short codes are generated for links without an alias.
+/// Selected with `SHORT_CODE_STRATEGY` (`sequential`, `random` or `obfuscated`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodeStrategy {
+    /// Base62 of the link id, the original behaviour.
+    Sequential,
+    /// Random base62 code of a fixed length (`SHORT_CODE_LENGTH`, default 8).
+    Random { length: usize },
+    /// Link id scrambled by a reversible permutation, padded to 8 characters.
+    Obfuscated { key: u64 },
+}
+
+impl CodeStrategy {
+    /// Read once at startup. The obfuscated strategy refuses to start without a
+    /// non-zero `SHORT_CODE_OBFUSCATION_KEY`: with a known key the codes are
+    /// trivially reversible from the public source.
+    pub fn from_env() -> Result<Self, String> {
+        let strategy = env::var("SHORT_CODE_STRATEGY").unwrap_or_default();
+        match strategy.trim().to_ascii_lowercase().as_str() {
+            "random" => {
+                let length = env::var("SHORT_CODE_LENGTH")
+                    .ok()
+                    .and_then(|v| v.parse::<usize>().ok())
+                    .unwrap_or(8)
+                    .clamp(4, 16);
+                Ok(CodeStrategy::Random { length })
+            }
+            "obfuscated" => {
+                let key = env::var("SHORT_CODE_OBFUSCATION_KEY")
+                    .ok()
+                    .and_then(|v| v.trim().parse::<u64>().ok())
+                    .filter(|&key| key != 0)
+                    .ok_or_else(|| {
+                        "SHORT_CODE_STRATEGY=obfuscated needs SHORT_CODE_OBFUSCATION_KEY set to a non-zero integer".to_string()
+                    })?;
+                Ok(CodeStrategy::Obfuscated { key })
+            }
+            _ => Ok(CodeStrategy::Sequential),
+        }
+    }
+
+    pub fn generate(self, id: i64) -> String {
+        match self {
+            CodeStrategy::Sequential => encode_base62(id),
+            CodeStrategy::Random { length } => random_code(length),
+            CodeStrategy::Obfuscated { key } => encode_obfuscated(id, key),
+        }
+    }
+}
+
+fn random_code(length: usize) -> String {
+    // A v4 UUID carries 122 random bits, enough for 16 base62 characters.
+    let mut bits = Uuid::new_v4().as_u128();
+    let base = BASE62_CHARSET.len() as u128;
+    (0..length)
+        .map(|_| {
+            let idx = (bits % base) as usize;
+            bits /= base;
+            BASE62_CHARSET[idx] as char
+        })
+        .collect()
+}
+
+fn obfuscation_modulus() -> u128 {
+    (BASE62_CHARSET.len() as u128).pow(OBFUSCATED_WIDTH)
+}
+
+fn obfuscation_half() -> u128 {
+    (BASE62_CHARSET.len() as u128).pow(OBFUSCATED_WIDTH / 2)
+}
+
+/// Swaps the high and low four base62 digits; a bijection on the code space.
+fn swap_halves(value: u128) -> u128 {
+    let half = obfuscation_half();
+    (value % half) * half + value / half
+}
+
+fn encode_obfuscated(id: i64, key: u64) -> String {
+    let modulus = obfuscation_modulus();
+    let mut value = id as u128 % modulus;
+    for _ in 0..OBFUSCATION_ROUNDS {
+        value = swap_halves((value * OBFUSCATION_MULTIPLIER + key as u128) % modulus);
+    }
+
+    let base = BASE62_CHARSET.len() as u128;
+    let mut buf = vec![BASE62_CHARSET[0]; OBFUSCATED_WIDTH as usize];
+    for slot in buf.iter_mut().rev() {
+        *slot = BASE62_CHARSET[(value % base) as usize];
+        value /= base;
+    }
+
+    String::from_utf8(buf).unwrap_or_default()
+}
+
+/// Inverse of `encode_obfuscated`; returns `None` for codes it could not have produced.
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn decode_obfuscated(code: &str, key: u64) -> Option<i64> {
+    if code.len() != OBFUSCATED_WIDTH as usize {
+        return None;
+    }
+
+    let modulus = obfuscation_modulus();
+    let base = BASE62_CHARSET.len() as u128;
+    let mut value: u128 = 0;
+    for byte in code.bytes() {
+        let idx = BASE62_CHARSET.iter().position(|&c| c == byte)? as u128;
+        value = value * base + idx;
+    }
+
+    let inverse = mod_inverse(OBFUSCATION_MULTIPLIER, modulus)?;
+    for _ in 0..OBFUSCATION_ROUNDS {
+        // swap_halves is its own inverse.
+        let shifted = (swap_halves(value) + modulus - key as u128 % modulus) % modulus;
+        value = shifted * inverse % modulus;
+    }
+    i64::try_from(value).ok()
+}
+
+fn mod_inverse(a: u128, modulus: u128) -> Option<u128> {
+    let (mut old_r, mut r) = (a as i128, modulus as i128);
+    let (mut old_s, mut s) = (1i128, 0i128);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_s, s) = (s, old_s - q * s);
+    }
+    if old_r != 1 {
+        return None;
+    }
+    Some(old_s.rem_euclid(modulus as i128) as u128)
+}
+
+const MAX_CODE_ATTEMPTS: usize = 5;
+
+/// Status used when the owner does not pick one; matches the column default.
+pub const DEFAULT_REDIRECT_TYPE: i16 = 307;
+pub const REDIRECT_TYPES: [i16; 4] = [301, 302, 307, 308];
+
+/// Optional settings a caller can attach to a new link.
+#[derive(Default, Clone)]
+pub struct LinkOptions {
+    pub alias: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub max_clicks: Option<i64>,
+    pub password: Option<String>,
+    pub redirect_type: Option<i16>,
+    pub cache_max_age: Option<i32>,
+}
+
+/// One item of a bulk create request.
+pub struct LinkInput {
+    pub original_url: String,
+    pub title: Option<String>,
+    pub options: LinkOptions,
+}
+
+pub enum BulkLinkOutcome {
+    Created(Link),
+    Existing(Link),
+    Failed(Error),
+}
+
+pub enum LinkResolution {
+    Found(Link),
+    /// A bot hit on a link with a click budget: it gets preview metadata only,
+    /// so crawlers neither use up the budget nor receive the destination.
+    Preview(Link),
+    Protected,
+    Expired,
+    NotFound,
+}
+
 pub async fn create_short_link(
     pool: &PgPool,
+    strategy: CodeStrategy,
     original_url: &str,
     owner_id: Option<i64>,
     title: Option<String>,
+    options: LinkOptions,
 ) -> Result<Link, Error> {
     if let Some(owner_id) = owner_id {
         if let Some(existing) = link_repository::find_by_owner_and_original_url(pool, owner_id, original_url).await? {
-            return Ok(existing);
+            if is_expired(&existing) {
+                // An expired link still holds the owner+URL slot; retire it so a fresh one can be issued.
+                link_repository::soft_delete_by_id(pool, existing.id).await?;
+            } else {
+                return reuse_existing(existing, &options);
+            }
+        }
+    }
+
+    let password_hash = hash_link_password(options.password.as_deref()).await?;
+
+    let alias = options.alias.as_deref();
+    if let Some(alias) = alias {
+        if link_repository::find_by_short_code(pool, alias).await?.is_some() {
+            return Err(Error::Io(std::io::Error::other("ALIAS_TAKEN")));
         }
     }
 
-    let id = link_repository::next_link_id(pool).await?;
-    let short_code = encode_base62(id);
-    match link_repository::create_with_id(pool, id, owner_id, original_url, &short_code, title).await {
-        Ok(link) => Ok(link),
-        Err(e) if is_unique_violation(&e) => {
-            if let Some(owner_id) = owner_id {
-                if let Some(existing) = link_repository::find_by_owner_and_original_url(pool, owner_id, original_url).await? {
-                    return Ok(existing);
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let id = link_repository::next_link_id(pool).await?;
+        let new_link = NewLink {
+            id,
+            owner_id,
+            original_url: original_url.to_string(),
+            short_code: alias.map(str::to_string).unwrap_or_else(|| strategy.generate(id)),
+            title: title.clone(),
+            expires_at: options.expires_at,
+            max_clicks: options.max_clicks,
+            password_hash: password_hash.clone(),
+            redirect_type: options.redirect_type.unwrap_or(DEFAULT_REDIRECT_TYPE),
+            cache_max_age: options.cache_max_age,
+        };
+
+        match link_repository::create_with_id(pool, &new_link).await {
+            Ok(link) => return Ok(link),
+            Err(e) if is_short_code_violation(&e) => {
+                if alias.is_some() {
+                    return Err(Error::Io(std::io::Error::other("ALIAS_TAKEN")));
                 }
+                // A generated code can land on an alias or an earlier random code; try again.
+                if attempts >= MAX_CODE_ATTEMPTS {
+                    return Err(e);
+                }
+            }
+            Err(e) if is_unique_violation(&e) => {
+                if let Some(owner_id) = owner_id {
+                    if let Some(existing) = link_repository::find_by_owner_and_original_url(pool, owner_id, original_url).await? {
+                        return reuse_existing(existing, &options);
+                    }
+                }
+                return Err(e);
             }
-            Err(e)
+            Err(e) => return Err(e),
         }
-        Err(e) => Err(e),
     }
 }
 
-pub async fn get_original_url(pool: &PgPool, short_code: &str) -> Result<Option<String>, Error> {
-    if let Some(link) = link_repository::find_active_by_short_code(pool, short_code).await? {
-        let today = current_date_vn();
-        let pool = pool.clone();
-        let link_id = link.id;
-        tokio::spawn(async move {
-            if let Err(e) = link_repository::increment_click_and_analytics(&pool, link_id, today).await {
-                tracing::warn!("Async analytics update failed: {:?}", e);
+/// Creates many links for one owner with a single id allocation and a single insert.
+/// Keeps the owner+URL dedupe of `create_short_link`, also between items of the same batch.
+/// Items the batch insert could not place fall back to `create_short_link` one by one.
+pub async fn create_short_links_bulk(
+    pool: &PgPool,
+    strategy: CodeStrategy,
+    owner_id: i64,
+    inputs: Vec<LinkInput>,
+) -> Result<Vec<BulkLinkOutcome>, Error> {
+    let mut outcomes: Vec<Option<BulkLinkOutcome>> = inputs.iter().map(|_| None).collect();
+
+    let mut original_urls: Vec<String> = inputs.iter().map(|input| input.original_url.clone()).collect();
+    original_urls.sort();
+    original_urls.dedup();
+
+    let mut existing_by_url: HashMap<String, Link> = HashMap::new();
+    let mut expired_ids = Vec::new();
+    for link in link_repository::find_active_by_owner_and_original_urls(pool, owner_id, &original_urls).await? {
+        if is_expired(&link) {
+            expired_ids.push(link.id);
+        } else {
+            existing_by_url.insert(link.original_url.clone(), link);
+        }
+    }
+    if !expired_ids.is_empty() {
+        link_repository::soft_delete_by_ids(pool, &expired_ids).await?;
+    }
+
+    let aliases: Vec<String> = inputs.iter().filter_map(|input| input.options.alias.clone()).collect();
+    let taken_aliases: HashSet<String> = if aliases.is_empty() {
+        HashSet::new()
+    } else {
+        link_repository::find_taken_short_codes(pool, &aliases).await?.into_iter().collect()
+    };
+
+    let mut claimed_urls: HashSet<&str> = HashSet::new();
+    let mut claimed_aliases: HashSet<&str> = HashSet::new();
+    let mut pending: Vec<usize> = Vec::new();
+    for (index, input) in inputs.iter().enumerate() {
+        if let Some(existing) = existing_by_url.get(&input.original_url) {
+            outcomes[index] = Some(match reuse_existing(existing.clone(), &input.options) {
+                Ok(link) => BulkLinkOutcome::Existing(link),
+                Err(e) => BulkLinkOutcome::Failed(e),
+            });
+            continue;
+        }
+        if claimed_urls.contains(input.original_url.as_str()) {
+            // Settled after the insert, against the item that claimed this URL first.
+            continue;
+        }
+        if let Some(alias) = input.options.alias.as_deref() {
+            if taken_aliases.contains(alias) || !claimed_aliases.insert(alias) {
+                outcomes[index] = Some(BulkLinkOutcome::Failed(Error::Io(std::io::Error::other("ALIAS_TAKEN"))));
+                continue;
             }
+        }
+        claimed_urls.insert(&input.original_url);
+        pending.push(index);
+    }
+
+    let ids = if pending.is_empty() {
+        Vec::new()
+    } else {
+        link_repository::next_link_ids(pool, pending.len() as i64).await?
+    };
+
+    // Items of a batch often share a password; hash each distinct one once.
+    let mut password_hashes: HashMap<&str, String> = HashMap::new();
+    for &index in &pending {
+        if let Some(password) = inputs[index].options.password.as_deref() {
+            if !password_hashes.contains_key(password) {
+                if let Some(hash) = hash_link_password(Some(password)).await? {
+                    password_hashes.insert(password, hash);
+                }
+            }
+        }
+    }
+
+    let mut new_links = Vec::with_capacity(pending.len());
+    for (&index, id) in pending.iter().zip(ids) {
+        let input = &inputs[index];
+        new_links.push(NewLink {
+            id,
+            owner_id: Some(owner_id),
+            original_url: input.original_url.clone(),
+            short_code: input.options.alias.clone().unwrap_or_else(|| strategy.generate(id)),
+            title: input.title.clone(),
+            expires_at: input.options.expires_at,
+            max_clicks: input.options.max_clicks,
+            password_hash: input.options.password.as_deref().and_then(|password| password_hashes.get(password).cloned()),
+            redirect_type: input.options.redirect_type.unwrap_or(DEFAULT_REDIRECT_TYPE),
+            cache_max_age: input.options.cache_max_age,
         });
-        Ok(Some(link.original_url))
+    }
+
+    let mut known_ids: HashSet<i64> = existing_by_url.values().map(|link| link.id).collect();
+    if !new_links.is_empty() {
+        let mut inserted: HashMap<i64, Link> = link_repository::create_many(pool, Some(owner_id), &new_links)
+            .await?
+            .into_iter()
+            .map(|link| (link.id, link))
+            .collect();
+        for (&index, new_link) in pending.iter().zip(&new_links) {
+            if let Some(link) = inserted.remove(&new_link.id) {
+                known_ids.insert(link.id);
+                outcomes[index] = Some(BulkLinkOutcome::Created(link));
+            }
+        }
+    }
+
+    let mut results = Vec::with_capacity(inputs.len());
+    for (input, outcome) in inputs.into_iter().zip(outcomes) {
+        if let Some(outcome) = outcome {
+            results.push(outcome);
+            continue;
+        }
+
+        let outcome = match create_short_link(pool, strategy, &input.original_url, Some(owner_id), input.title, input.options).await {
+            Ok(link) if known_ids.contains(&link.id) => BulkLinkOutcome::Existing(link),
+            Ok(link) => {
+                known_ids.insert(link.id);
+                BulkLinkOutcome::Created(link)
+            }
+            Err(e) => BulkLinkOutcome::Failed(e),
+        };
+        results.push(outcome);
+    }
+
+    Ok(results)
+}
+
+/// bcrypt takes hundreds of milliseconds, so it runs on the blocking pool.
+async fn hash_link_password(password: Option<&str>) -> Result<Option<String>, Error> {
+    let Some(password) = password.map(str::to_string) else {
+        return Ok(None);
+    };
+    tokio::task::spawn_blocking(move || bcrypt::hash(password, bcrypt::DEFAULT_COST))
+        .await
+        .map_err(|e| Error::Io(std::io::Error::other(format!("bcrypt task failed: {e}"))))?
+        .map(Some)
+        .map_err(|e| Error::Io(std::io::Error::other(format!("bcrypt error: {e}"))))
+}
+
+/// Checks an unlock attempt on the blocking pool, like `hash_link_password`;
+/// a malformed hash counts as a wrong password.
+async fn verify_link_password(password: &str, password_hash: &str) -> Result<bool, Error> {
+    let (password, password_hash) = (password.to_string(), password_hash.to_string());
+    tokio::task::spawn_blocking(move || bcrypt::verify(password, &password_hash).unwrap_or(false))
+        .await
+        .map_err(|e| Error::Io(std::io::Error::other(format!("bcrypt task failed: {e}"))))
+}
+
+fn reuse_existing(existing: Link, options: &LinkOptions) -> Result<Link, Error> {
+    let same_code = options.alias.as_deref().is_none_or(|alias| alias == existing.short_code);
+    let plain = options.expires_at.is_none()
+        && options.max_clicks.is_none()
+        && options.password.is_none()
+        && options.redirect_type.is_none()
+        && options.cache_max_age.is_none();
+    if same_code && plain {
+        Ok(existing)
     } else {
-        Ok(None)
+        Err(Error::Io(std::io::Error::other("LINK_EXISTS")))
     }
 }
 
+pub fn is_expired(link: &Link) -> bool {
+    link.expires_at.is_some_and(|expires_at| expires_at <= Utc::now())
+        || link.max_clicks.is_some_and(|max| link.click_count.unwrap_or(0) >= max)
+}
+
+/// Whether a link's destination may be served straight from the Redis cache.
+/// Budgeted links must be counted in the database and protected links need a password check.
+pub fn is_cacheable(link: &Link) -> bool {
+    link.max_clicks.is_none() && link.password_hash.is_none()
+}
+
+/// Looks up a short code for redirecting and counts the click.
+/// Protected links only resolve when `password` matches; without one they report `Protected`.
+/// Links with a click budget are counted before answering so the budget cannot be overrun.
+/// Bot hits never use up a budget, so on those links they only get a `Preview`;
+/// all other clicks are buffered through `click_service`.
+pub async fn resolve_short_code(
+    pool: &PgPool,
+    redis: &deadpool_redis::Pool,
+    short_code: &str,
+    password: Option<&str>,
+    is_bot: bool,
+) -> Result<LinkResolution, Error> {
+    let link = match link_repository::find_active_by_short_code(pool, short_code).await? {
+        Some(link) => link,
+        None => {
+            return match link_repository::find_by_short_code(pool, short_code).await? {
+                Some(link) if link.is_active != Some(false) && is_expired(&link) => Ok(LinkResolution::Expired),
+                _ => Ok(LinkResolution::NotFound),
+            };
+        }
+    };
+
+    if let Some(password_hash) = link.password_hash.as_deref() {
+        let Some(password) = password else {
+            return Ok(LinkResolution::Protected);
+        };
+        if !verify_link_password(password, password_hash).await? {
+            return Err(Error::Io(std::io::Error::other("PASSWORD_INVALID")));
+        }
+    }
+
+    if link.max_clicks.is_some() {
+        if is_bot {
+            click_service::record_click(pool, redis, link.id, true);
+            return Ok(LinkResolution::Preview(link));
+        }
+        if !link_repository::increment_click_and_analytics(pool, link.id, click_service::current_hour()).await? {
+            return Ok(LinkResolution::Expired);
+        }
+    } else {
+        click_service::record_click(pool, redis, link.id, is_bot);
+    }
+
+    Ok(LinkResolution::Found(link))
+}
+
 #[allow(dead_code)]
 pub async fn get_link_details(pool: &PgPool, short_code: &str) -> Result<Option<Link>, Error> {
     link_repository::find_by_short_code(pool, short_code).await
 }
 
-pub async fn get_user_links(pool: &PgPool, user_id: i64) -> Result<Vec<Link>, Error> {
-    link_repository::get_all_by_user(pool, user_id).await
+/// Returns one page of the user's links, the total match count and the cursor of the next page.
+pub async fn search_user_links(
+    pool: &PgPool,
+    user_id: i64,
+    mut search: LinkSearch,
+) -> Result<(Vec<Link>, i64, Option<LinkCursor>), Error> {
+    let page_size = search.limit;
+    // Fetch one extra row to learn whether another page exists.
+    search.limit = page_size + 1;
+    let (mut links, total) = link_repository::search_by_user(pool, user_id, &search).await?;
+
+    let next_cursor = if links.len() as i64 > page_size {
+        links.truncate(page_size as usize);
+        links.last().map(|link| LinkCursor {
+            sort_value: match search.sort {
+                LinkSortField::Created => link.created_at.timestamp_micros(),
+                LinkSortField::Updated => link.updated_at.timestamp_micros(),
+                LinkSortField::Clicks => link.click_count.unwrap_or(0),
+            },
+            id: link.id,
+        })
+    } else {
+        None
+    };
+
+    Ok((links, total, next_cursor))
+}
+
+/// The link if it exists and belongs to `user_id`.
+pub async fn get_user_link(pool: &PgPool, user_id: i64, link_id: i64) -> Result<Option<Link>, Error> {
+    link_repository::find_by_id_and_owner(pool, link_id, user_id).await
+}
+
+/// All of the user's links for export, streamed so large accounts are never held in memory.
+pub fn stream_user_links(pool: &PgPool, user_id: i64) -> BoxStream<'_, Result<Link, Error>> {
+    link_repository::stream_by_owner(pool, user_id)
+}
+
+pub async fn get_user_trash(pool: &PgPool, user_id: i64) -> Result<Vec<Link>, Error> {
+    link_repository::get_inactive_by_user(pool, user_id).await
 }
 
 pub async fn get_all_links(pool: &PgPool) -> Result<Vec<Link>, Error> {
     link_repository::get_all(pool).await
 }
 
+/// Updates the user's link. Fails with `LINK_DISABLED_BY_ADMIN` when asked to
+/// re-activate a link an admin took down.
+pub async fn update_link(
+    pool: &PgPool,
+    user_id: i64,
+    link_id: i64,
+    changes: &LinkChanges,
+) -> Result<Option<Link>, Error> {
+    match link_repository::update_by_owner(pool, link_id, user_id, changes).await {
+        Ok(None) if changes.is_active == Some(true) => {
+            if link_repository::is_disabled_by_admin(pool, link_id, user_id).await? {
+                Err(Error::Io(std::io::Error::other("LINK_DISABLED_BY_ADMIN")))
+            } else {
+                Ok(None)
+            }
+        }
+        Err(e) if is_unique_violation(&e) => Err(Error::Io(std::io::Error::other("LINK_EXISTS"))),
+        result => result,
+    }
+}
+
 pub async fn soft_delete_link(pool: &PgPool, user_id: i64, link_id: i64) -> Result<Option<Link>, Error> {
     link_repository::soft_delete_by_owner(pool, link_id, user_id).await
 }
 
 pub async fn admin_soft_delete_link(pool: &PgPool, link_id: i64) -> Result<Option<Link>, Error> {
-    link_repository::soft_delete_by_id(pool, link_id).await
+    link_repository::disable_by_id(pool, link_id).await
 }
 
-pub async fn get_daily_analytics(
+/// Reactivates a soft-deleted link, unless an admin disabled it. Fails with
+/// `LINK_EXISTS` when the owner already has another active link for the same URL.
+pub async fn restore_link(pool: &PgPool, user_id: i64, link_id: i64) -> Result<Option<Link>, Error> {
+    match link_repository::restore_by_owner(pool, link_id, user_id).await {
+        Err(e) if is_unique_violation(&e) => Err(Error::Io(std::io::Error::other("LINK_EXISTS"))),
+        result => result,
+    }
+}
+
+/// Reactivates any inactive link, lifting an admin takedown as well.
+pub async fn admin_restore_link(pool: &PgPool, link_id: i64) -> Result<Option<Link>, Error> {
+    match link_repository::restore_by_id(pool, link_id).await {
+        Err(e) if is_unique_violation(&e) => Err(Error::Io(std::io::Error::other("LINK_EXISTS"))),
+        result => result,
+    }
+}
+
+/// Clicks and approximate unique visitors of one bucket.
+pub struct AnalyticsBucket {
+    /// Local start of the bucket.
+    pub period: NaiveDateTime,
+    pub total_clicks: i64,
+    /// `None` when the visitor sketches could not be counted in Redis, or the
+    /// range covers more than `MAX_VISITOR_SKETCHES` of them.
+    pub uniques: Option<i64>,
+}
+
+/// Most visitor sketches one analytics request may load into Redis; past that
+/// the series comes back without uniques.
+const MAX_VISITOR_SKETCHES: i64 = 5000;
+
+/// Click series over the owner's links, one zero-filled bucket per hour, day,
+/// week or month. Unique visitors never include bots, whatever
+/// `filter.include_bots` says, since bot hits are not fingerprinted. Day, week
+/// and month uniques are counted on UTC days.
+pub async fn get_analytics_series(
     pool: &PgPool,
-    user_id: i64,
-    from_date: NaiveDate,
-    to_date: NaiveDate,
-) -> Result<Vec<DailyClickTotal>, Error> {
-    link_repository::get_daily_analytics_by_user(pool, user_id, from_date, to_date).await
+    redis: &deadpool_redis::Pool,
+    filter: &ClickEventFilter,
+    granularity: AnalyticsGranularity,
+) -> Result<Vec<AnalyticsBucket>, Error> {
+    let series = link_repository::get_click_series(pool, filter, granularity).await?;
+
+    let sketches = link_repository::get_visitor_sketches(pool, filter, granularity, MAX_VISITOR_SKETCHES + 1).await?;
+    let uniques = if sketches.len() as i64 > MAX_VISITOR_SKETCHES {
+        None
+    } else {
+        match click_service::count_uniques(redis, sketches).await {
+            Ok(uniques) => Some(uniques),
+            Err(e) => {
+                tracing::warn!("Unique visitor count failed: {:?}", e);
+                None
+            }
+        }
+    };
+
+    Ok(series
+        .into_iter()
+        .map(|point| AnalyticsBucket {
+            period: point.period,
+            total_clicks: point.clicks,
+            uniques: uniques.as_ref().map(|uniques| uniques.get(&point.period).copied().unwrap_or(0)),
+        })
+        .collect())
+}
+
+/// Per-link click series for export, streamed. `filter.include_bots` is not
+/// applied: every row carries both the human and the bot count.
+pub fn stream_click_series<'a>(
+    pool: &'a PgPool,
+    filter: &'a ClickEventFilter,
+    granularity: AnalyticsGranularity,
+) -> BoxStream<'a, Result<LinkClickSeriesRow, Error>> {
+    link_repository::stream_click_series_by_owner(
+        pool,
+        filter.owner_id,
+        filter.from_date,
+        filter.to_date,
+        &filter.timezone,
+        filter.tag_id,
+        granularity,
+    )
+}
+
+/// Click analytics for one link over a date range.
+pub struct LinkAnalyticsReport {
+    pub link: Link,
+    pub series: Vec<ClickSeriesPoint>,
+    pub total_clicks: i64,
+    pub previous_from: NaiveDate,
+    pub previous_to: NaiveDate,
+    pub previous_total_clicks: i64,
+    pub first_click_at: Option<DateTime<Utc>>,
+    pub last_click_at: Option<DateTime<Utc>>,
+}
+
+/// Analytics for `filter.link_id`. Returns `None` when no link is given, or it does not exist or belongs to someone else.
+/// Dates are local days in the filter's timezone. The previous period is the same number of days ending the day before `from_date`.
+pub async fn get_link_analytics(
+    pool: &PgPool,
+    filter: &ClickEventFilter,
+    granularity: AnalyticsGranularity,
+) -> Result<Option<LinkAnalyticsReport>, Error> {
+    let Some(link_id) = filter.link_id else {
+        return Ok(None);
+    };
+    let Some(link) = link_repository::find_by_id_and_owner(pool, link_id, filter.owner_id).await? else {
+        return Ok(None);
+    };
+    let ClickEventFilter { from_date, to_date, ref timezone, include_bots, .. } = *filter;
+
+    let series = link_repository::get_click_series(pool, filter, granularity).await?;
+    let total_clicks =
+        link_repository::get_clicks_total_by_link(pool, link_id, from_date, to_date, timezone, include_bots).await?;
+
+    let previous_to = from_date - chrono::Duration::days(1);
+    let previous_from = previous_to - (to_date - from_date);
+    let previous_total_clicks =
+        link_repository::get_clicks_total_by_link(pool, link_id, previous_from, previous_to, timezone, include_bots).await?;
+
+    let (first_click_at, last_click_at) = link_repository::get_click_time_bounds_by_link(pool, link_id).await?;
+
+    Ok(Some(LinkAnalyticsReport {
+        link,
+        series,
+        total_clicks,
+        previous_from,
+        previous_to,
+        previous_total_clicks,
+        first_click_at,
+        last_click_at,
+    }))
 }
 
-fn current_date_vn() -> NaiveDate {
-    let offset = FixedOffset::east_opt(7 * 3600).unwrap_or_else(|| FixedOffset::east_opt(0).unwrap());
-    Utc::now().with_timezone(&offset).date_naive()
+/// Label of the bucket that collects everything outside the top entries of a
+/// breakdown. It is the parser's own label for unrecognised browsers and OSes,
+/// so those clicks land in the same bucket instead of a second "Other" row.
+pub const BREAKDOWN_OTHER_LABEL: &str = user_agent::OTHER;
+
+/// One row of a referrer/device/browser/OS breakdown.
+pub struct BreakdownItem {
+    pub label: String,
+    pub clicks: i64,
 }
 
-fn is_unique_violation(err: &Error) -> bool {
+/// Click events grouped by `dimension`, biggest first. Only the top `limit`
+/// labels are kept; the rest, and values the parser could not place, are
+/// summed into a final `Other` row.
+/// Returns `None` when `filter.link_id` names a link the owner does not have.
+pub async fn get_click_breakdown(
+    pool: &PgPool,
+    filter: &ClickEventFilter,
+    dimension: BreakdownDimension,
+    limit: usize,
+    with_versions: bool,
+) -> Result<Option<Vec<BreakdownItem>>, Error> {
+    if let Some(link_id) = filter.link_id {
+        if link_repository::find_by_id_and_owner(pool, link_id, filter.owner_id).await?.is_none() {
+            return Ok(None);
+        }
+    }
+
+    let rows = click_event_repository::count_by_dimension(pool, filter, dimension, with_versions).await?;
+
+    let mut totals: HashMap<String, i64> = HashMap::new();
+    for row in rows {
+        let label = if row.rolled_up {
+            rolled_up_label(dimension, row.value)
+        } else {
+            breakdown_label(dimension, row.value.as_deref(), with_versions)
+        };
+        *totals.entry(label).or_default() += row.clicks;
+    }
+
+    let mut other_clicks = totals.remove(BREAKDOWN_OTHER_LABEL).unwrap_or(0);
+    let mut items: Vec<BreakdownItem> = totals
+        .into_iter()
+        .map(|(label, clicks)| BreakdownItem { label, clicks })
+        .collect();
+    items.sort_by(|a, b| b.clicks.cmp(&a.clicks).then_with(|| a.label.cmp(&b.label)));
+
+    if items.len() > limit {
+        other_clicks += items.split_off(limit).iter().map(|item| item.clicks).sum::<i64>();
+    }
+    if other_clicks > 0 {
+        items.push(BreakdownItem {
+            label: BREAKDOWN_OTHER_LABEL.to_string(),
+            clicks: other_clicks,
+        });
+    }
+
+    Ok(Some(items))
+}
+
+fn breakdown_label(dimension: BreakdownDimension, value: Option<&str>, with_versions: bool) -> String {
+    if dimension == BreakdownDimension::Referrers {
+        return value.map_or_else(|| "(direct)".to_string(), client_info::normalize_referrer_host);
+    }
+
+    let Some(info) = value.map(user_agent::parse) else {
+        return "unknown".to_string();
+    };
+    match dimension {
+        BreakdownDimension::Devices => info.device.as_str().to_string(),
+        BreakdownDimension::Os => info.os.to_string(),
+        _ => match info.browser_version {
+            Some(version) if with_versions => format!("{} {}", info.browser, version),
+            _ => info.browser.to_string(),
+        },
+    }
+}
+
+/// Rollups already store labels; only the missing ones need naming, the same
+/// way `breakdown_label` names them.
+fn rolled_up_label(dimension: BreakdownDimension, value: Option<String>) -> String {
+    match (dimension, value) {
+        (_, Some(label)) => label,
+        (BreakdownDimension::Referrers, None) => "(direct)".to_string(),
+        (_, None) => "unknown".to_string(),
+    }
+}
+
+fn is_short_code_violation(err: &Error) -> bool {
     match err {
-        Error::Database(db_err) => db_err.code().as_deref() == Some("23505"),
+        Error::Database(db_err) => is_unique_violation(err) && db_err.constraint() == Some("links_short_code_key"),
         _ => false,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: u64 = 0x5eed_1234_abcd;
+
+    #[test]
+    fn obfuscated_codes_round_trip() {
+        let ids = [1, 2, 61, 62, 3_843, 1_000_000, 218_340_105_584_895, (obfuscation_modulus() - 1) as i64];
+        for id in ids {
+            let code = encode_obfuscated(id, KEY);
+            assert_eq!(code.len(), OBFUSCATED_WIDTH as usize);
+            assert_eq!(decode_obfuscated(&code, KEY), Some(id), "id {id} via {code}");
+        }
+    }
+
+    #[test]
+    fn obfuscated_codes_depend_on_the_key() {
+        let code = encode_obfuscated(42, KEY);
+        assert_ne!(code, encode_obfuscated(42, KEY + 1));
+        assert_ne!(decode_obfuscated(&code, KEY + 1), Some(42));
+    }
+
+    #[test]
+    fn decode_obfuscated_rejects_foreign_codes() {
+        assert_eq!(decode_obfuscated("abc", KEY), None);
+        assert_eq!(decode_obfuscated("abcd-fgh", KEY), None);
+    }
+}
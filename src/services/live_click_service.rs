@@ -0,0 +1,186 @@
+use chrono::{DateTime, Utc};
+use deadpool_redis::redis::{self, AsyncCommands};
+use futures_util::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, watch, Mutex};
+use tokio::task::JoinHandle;
+
+use crate::services::click_event_service::ClickContext;
+use crate::utils::user_agent::{self, DeviceClass};
+
+#[derive(Debug, thiserror::Error)]
+pub enum LiveClickError {
+    #[error("Redis pool error: {0}")]
+    Pool(#[from] deadpool_redis::PoolError),
+
+    #[error("Redis error: {0}")]
+    Redis(#[from] redis::RedisError),
+
+    #[error("Live click serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+/// Every click on an owner's links is published on `clicks:live:{owner_id}`,
+/// so any app instance can serve the live stream.
+const LIVE_CHANNEL_PREFIX: &str = "clicks:live:";
+
+/// Clicks buffered per owner for streams that fall behind; past that the
+/// slowest ones skip ahead.
+const LIVE_BUFFER: usize = 256;
+
+/// Wait before re-subscribing after the Redis connection drops.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// One click as shown on a live dashboard.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiveClick {
+    pub link_id: i64,
+    pub clicked_at: DateTime<Utc>,
+    pub referrer_host: Option<String>,
+    /// desktop, mobile, tablet, bot or unknown.
+    pub device: String,
+    pub is_bot: bool,
+}
+
+impl LiveClick {
+    pub fn new(link_id: i64, context: &ClickContext) -> Self {
+        let device = if context.is_bot {
+            DeviceClass::Bot
+        } else {
+            context
+                .user_agent
+                .as_deref()
+                .map_or(DeviceClass::Unknown, |ua| user_agent::parse(ua).device)
+        };
+
+        Self {
+            link_id,
+            clicked_at: Utc::now(),
+            referrer_host: context.referrer_host.clone(),
+            device: device.as_str().to_string(),
+            is_bot: context.is_bot,
+        }
+    }
+}
+
+/// Holds one Redis pub/sub connection per app instance, subscribed to every
+/// owner's channel, and fans clicks out to the local streams of that owner.
+#[derive(Clone)]
+pub struct LiveClickHub {
+    client: redis::Client,
+    streams: Arc<Mutex<HashMap<i64, broadcast::Sender<LiveClick>>>>,
+    /// Flipped to `true` by `close` so open streams end and shutdown can finish.
+    closed: Arc<watch::Sender<bool>>,
+}
+
+impl LiveClickHub {
+    pub fn new(redis_url: &str) -> redis::RedisResult<Self> {
+        Ok(Self {
+            client: redis::Client::open(redis_url)?,
+            streams: Arc::new(Mutex::new(HashMap::new())),
+            closed: Arc::new(watch::channel(false).0),
+        })
+    }
+
+    /// Clicks on the owner's links from now on, until the hub is closed.
+    /// Clicks published while the shared connection is down are missed.
+    pub async fn subscribe(&self, owner_id: i64) -> impl Stream<Item = LiveClick> {
+        let receiver = {
+            let mut streams = self.streams.lock().await;
+            streams.retain(|_, sender| sender.receiver_count() > 0);
+            streams
+                .entry(owner_id)
+                .or_insert_with(|| broadcast::channel(LIVE_BUFFER).0)
+                .subscribe()
+        };
+
+        let mut closed = self.closed.subscribe();
+        let shutdown = async move {
+            // An error means the hub itself is gone, which also ends the stream.
+            let _ = closed.wait_for(|&closed| closed).await;
+        };
+
+        futures_util::stream::unfold(receiver, |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(click) => return Some((click, receiver)),
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        })
+        .take_until(shutdown)
+    }
+
+    /// Keeps the shared subscription alive, reconnecting whenever Redis drops it.
+    pub fn spawn_listener(&self) -> JoinHandle<()> {
+        let hub = self.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = hub.listen().await {
+                    tracing::warn!("Live click subscription failed: {:?}", e);
+                }
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        })
+    }
+
+    /// Forwards every published click to the streams of its owner; messages
+    /// that do not parse are skipped.
+    async fn listen(&self) -> redis::RedisResult<()> {
+        let mut pubsub = self.client.get_async_connection().await?.into_pubsub();
+        pubsub.psubscribe(format!("{}*", LIVE_CHANNEL_PREFIX)).await?;
+
+        let mut messages = pubsub.into_on_message();
+        while let Some(message) = messages.next().await {
+            let Some(owner_id) = message
+                .get_channel_name()
+                .strip_prefix(LIVE_CHANNEL_PREFIX)
+                .and_then(|id| id.parse::<i64>().ok())
+            else {
+                continue;
+            };
+            let Some(click) = message
+                .get_payload::<String>()
+                .ok()
+                .and_then(|payload| serde_json::from_str::<LiveClick>(&payload).ok())
+            else {
+                continue;
+            };
+
+            if let Some(sender) = self.streams.lock().await.get(&owner_id) {
+                // No receivers left only means the owner's streams just closed.
+                let _ = sender.send(click);
+            }
+        }
+        tracing::warn!("Live click subscription closed");
+        Ok(())
+    }
+
+    /// Ends every open live stream, and any opened later. Called on shutdown,
+    /// which otherwise waits for the streams to finish on their own.
+    pub fn close(&self) {
+        self.closed.send_replace(true);
+    }
+}
+
+/// Publishes a click in the background. Nothing is retried: a live stream
+/// that misses a click is still correct once analytics catch up.
+pub fn publish(redis: &deadpool_redis::Pool, owner_id: i64, click: LiveClick) {
+    let redis = redis.clone();
+    tokio::spawn(async move {
+        if let Err(e) = publish_click(&redis, owner_id, &click).await {
+            tracing::warn!("Live click publish failed: {:?}", e);
+        }
+    });
+}
+
+async fn publish_click(redis: &deadpool_redis::Pool, owner_id: i64, click: &LiveClick) -> Result<(), LiveClickError> {
+    let payload = serde_json::to_string(click)?;
+    let mut conn = redis.get().await?;
+    conn.publish::<_, _, ()>(format!("{}{}", LIVE_CHANNEL_PREFIX, owner_id), payload).await?;
+    Ok(())
+}
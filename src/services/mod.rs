@@ -1,3 +1,10 @@
 pub mod user_service;
 pub mod link_service;
 pub mod cache_service;
+pub mod tag_service;
+pub mod click_service;
+pub mod click_event_service;
+pub mod live_click_service;
+pub mod retention_service;
+pub mod alert_service;
+pub mod api_key_service;
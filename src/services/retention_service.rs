@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::{DateTime, DurationRound, TimeDelta, Utc};
+use sqlx::{Error, PgPool};
+use tokio::task::JoinHandle;
+
+use crate::models::click_event::{ClickRollupKey, PrunedClickEvent, RetentionCandidates, RetentionMode};
+use crate::repositories::click_event_repository;
+use crate::utils::{client_info, user_agent};
+
+const DEFAULT_RETENTION_DAYS: i64 = 90;
+const DEFAULT_BATCH_SIZE: i64 = 5000;
+const DEFAULT_RETENTION_INTERVAL_SECS: u64 = 3600;
+/// Longest retention accepted (about a century), keeping `cutoff` well inside
+/// the range `DateTime` can represent.
+pub const MAX_RETENTION_DAYS: i64 = 36500;
+
+/// How long raw click events are kept and what happens to them afterwards.
+/// Click counters live in `link_click_hours` and are never touched.
+#[derive(Debug, Clone, Copy)]
+pub struct RetentionPolicy {
+    pub retain_days: i64,
+    pub mode: RetentionMode,
+    /// Events handled per statement, so a large backlog never holds locks for long.
+    pub batch_size: i64,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            retain_days: DEFAULT_RETENTION_DAYS,
+            mode: RetentionMode::Delete,
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// Policy from `CLICK_RETENTION_DAYS` (default 90, 0 turns the job off, at
+    /// most `MAX_RETENTION_DAYS`),
+    /// `CLICK_RETENTION_MODE` (delete | anonymize, default delete) and
+    /// `CLICK_RETENTION_BATCH_SIZE` (default 5000). An unknown mode also turns the
+    /// job off rather than guessing how much data to destroy.
+    pub fn from_env() -> Option<Self> {
+        let defaults = Self::default();
+        let retain_days = std::env::var("CLICK_RETENTION_DAYS")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .filter(|v| (0..=MAX_RETENTION_DAYS).contains(v))
+            .unwrap_or(defaults.retain_days);
+        if retain_days == 0 {
+            return None;
+        }
+
+        let mode = match std::env::var("CLICK_RETENTION_MODE") {
+            Ok(value) => match RetentionMode::parse(&value) {
+                Some(mode) => mode,
+                None => {
+                    tracing::warn!("Unknown CLICK_RETENTION_MODE {:?}, click retention is disabled", value);
+                    return None;
+                }
+            },
+            Err(_) => defaults.mode,
+        };
+
+        let batch_size = std::env::var("CLICK_RETENTION_BATCH_SIZE")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .filter(|&v| v > 0)
+            .unwrap_or(defaults.batch_size);
+
+        Some(Self { retain_days, mode, batch_size })
+    }
+
+    /// Events clicked before this instant are past retention.
+    pub fn cutoff(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now - TimeDelta::days(self.retain_days)
+    }
+}
+
+/// Outcome of one retention run.
+#[derive(Debug)]
+pub struct RetentionReport {
+    pub cutoff: DateTime<Utc>,
+    /// Events deleted (after rollup) or anonymized, depending on the mode.
+    pub events: u64,
+    /// Rollup rows created or added to (delete mode only).
+    pub rollup_rows: u64,
+    pub batches: u64,
+}
+
+/// Applies the policy to every event older than its cutoff, one batch at a time.
+pub async fn run_retention(pool: &PgPool, policy: &RetentionPolicy) -> Result<RetentionReport, Error> {
+    let cutoff = policy.cutoff(Utc::now());
+    let mut report = RetentionReport { cutoff, events: 0, rollup_rows: 0, batches: 0 };
+
+    loop {
+        let handled = match policy.mode {
+            RetentionMode::Delete => {
+                let (events, rollup_rows) =
+                    click_event_repository::roll_up_and_delete_before(pool, cutoff, policy.batch_size, summarize_events)
+                        .await?;
+                report.rollup_rows += rollup_rows as u64;
+                events as u64
+            }
+            RetentionMode::Anonymize => click_event_repository::anonymize_before(pool, cutoff, policy.batch_size).await?,
+        };
+        if handled == 0 {
+            break;
+        }
+        report.events += handled;
+        report.batches += 1;
+        if handled < policy.batch_size as u64 {
+            break;
+        }
+    }
+
+    Ok(report)
+}
+
+/// Groups pruned events by what the breakdowns show, so raw user agents, which
+/// are nearly unique, collapse into a handful of browser/OS/device rows.
+fn summarize_events(events: &[PrunedClickEvent]) -> Vec<(ClickRollupKey, i32)> {
+    let mut rollups: HashMap<ClickRollupKey, i32> = HashMap::new();
+    for event in events {
+        let agent = event.user_agent.as_deref().map(user_agent::parse);
+        let key = ClickRollupKey {
+            link_id: event.link_id,
+            hour: event.clicked_at.duration_trunc(TimeDelta::hours(1)).unwrap_or(event.clicked_at),
+            referrer_host: event.referrer_host.as_deref().map(client_info::normalize_referrer_host),
+            browser: agent.as_ref().map(|agent| agent.browser.to_string()),
+            browser_version: agent.as_ref().and_then(|agent| agent.browser_version.clone()),
+            os: agent.as_ref().map(|agent| agent.os.to_string()),
+            device: agent.as_ref().map(|agent| agent.device.as_str().to_string()),
+            is_bot: event.is_bot,
+        };
+        *rollups.entry(key).or_default() += 1;
+    }
+    rollups.into_iter().collect()
+}
+
+/// What `run_retention` would do with this policy right now, without changing anything.
+pub async fn preview_retention(
+    pool: &PgPool,
+    policy: &RetentionPolicy,
+) -> Result<(DateTime<Utc>, RetentionCandidates), Error> {
+    let cutoff = policy.cutoff(Utc::now());
+    let candidates = click_event_repository::find_retention_candidates(pool, cutoff).await?;
+    Ok((cutoff, candidates))
+}
+
+/// Run interval from `CLICK_RETENTION_INTERVAL_SECS` (default 1 hour).
+pub fn retention_interval_from_env() -> Duration {
+    let secs = std::env::var("CLICK_RETENTION_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|&v| v > 0)
+        .unwrap_or(DEFAULT_RETENTION_INTERVAL_SECS);
+    Duration::from_secs(secs)
+}
+
+/// Runs `run_retention` on a fixed interval until the task is aborted. Several
+/// app instances may run it at once: batches skip rows another instance holds.
+pub fn spawn_retention_job(pool: PgPool, policy: RetentionPolicy, interval: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match run_retention(&pool, &policy).await {
+                Ok(report) if report.events > 0 => tracing::info!(
+                    "Click retention ({}): {} events older than {} in {} batches, {} rollup rows",
+                    policy.mode.as_str(),
+                    report.events,
+                    report.cutoff,
+                    report.batches,
+                    report.rollup_rows
+                ),
+                Ok(_) => tracing::debug!("Click retention ({}): nothing to do", policy.mode.as_str()),
+                Err(e) => tracing::warn!("Click retention failed: {:?}", e),
+            }
+        }
+    })
+}
@@ -0,0 +1,56 @@
+use sqlx::{PgPool, Error};
+use chrono::NaiveDate;
+use crate::db::is_unique_violation;
+use crate::models::tag::{Tag, TagClickTotal};
+use crate::repositories::{link_repository, tag_repository};
+
+pub async fn create_tag(pool: &PgPool, user_id: i64, name: &str, color: Option<&str>) -> Result<Tag, Error> {
+    match tag_repository::create(pool, user_id, name.trim(), color).await {
+        Err(e) if is_unique_violation(&e) => Err(Error::Io(std::io::Error::other("TAG_EXISTS"))),
+        result => result,
+    }
+}
+
+pub async fn list_tags(pool: &PgPool, user_id: i64) -> Result<Vec<Tag>, Error> {
+    tag_repository::get_all_by_owner(pool, user_id).await
+}
+
+pub async fn update_tag(
+    pool: &PgPool,
+    user_id: i64,
+    tag_id: i64,
+    name: Option<&str>,
+    color: Option<&str>,
+) -> Result<Option<Tag>, Error> {
+    match tag_repository::update_by_owner(pool, tag_id, user_id, name.map(str::trim), color).await {
+        Err(e) if is_unique_violation(&e) => Err(Error::Io(std::io::Error::other("TAG_EXISTS"))),
+        result => result,
+    }
+}
+
+pub async fn delete_tag(pool: &PgPool, user_id: i64, tag_id: i64) -> Result<bool, Error> {
+    tag_repository::delete_by_owner(pool, tag_id, user_id).await
+}
+
+pub async fn get_link_tags(pool: &PgPool, user_id: i64, link_id: i64) -> Result<Vec<Tag>, Error> {
+    tag_repository::get_by_link(pool, user_id, link_id).await
+}
+
+pub async fn assign_tag(pool: &PgPool, user_id: i64, link_id: i64, tag_id: i64) -> Result<bool, Error> {
+    tag_repository::assign_to_link(pool, user_id, link_id, tag_id).await
+}
+
+pub async fn unassign_tag(pool: &PgPool, user_id: i64, link_id: i64, tag_id: i64) -> Result<bool, Error> {
+    tag_repository::remove_from_link(pool, user_id, link_id, tag_id).await
+}
+
+pub async fn get_tag_analytics(
+    pool: &PgPool,
+    user_id: i64,
+    from_date: NaiveDate,
+    to_date: NaiveDate,
+    timezone: &str,
+    include_bots: bool,
+) -> Result<Vec<TagClickTotal>, Error> {
+    link_repository::get_tag_analytics_by_user(pool, user_id, from_date, to_date, timezone, include_bots).await
+}
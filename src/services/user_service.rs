@@ -1,7 +1,9 @@
 use chrono::{DateTime, Duration, Utc};
 use sha2::{Digest, Sha256};
 use sqlx::PgPool;
+use uuid::Uuid;
 use crate::repositories::user_repository;
+use crate::models::refresh_tokens::RefreshRotation;
 use crate::models::user::User;
 use crate::dtos::user::{LoginResponse, RefreshTokenResponse};
 use crate::utils::jwt::{encode_access_token, encode_refresh_token, decode_refresh_token};
@@ -23,6 +25,30 @@ pub async fn list_users(pool: &PgPool) -> Result<Vec<User>, sqlx::Error> {
     user_repository::get_all(pool).await
 }
 
+/// Fails with `TIMEZONE_INVALID` when the name is not a known IANA timezone.
+pub async fn update_timezone(pool: &PgPool, user_id: i64, timezone: &str) -> Result<Option<User>, sqlx::Error> {
+    if !user_repository::timezone_exists(pool, timezone).await? {
+        return Err(sqlx::Error::Io(std::io::Error::other("TIMEZONE_INVALID")));
+    }
+    user_repository::update_timezone(pool, user_id, timezone).await
+}
+
+/// Timezone analytics should use: the requested one if given, otherwise the user's default.
+/// Fails with `TIMEZONE_INVALID` when the requested name is unknown.
+pub async fn resolve_timezone(pool: &PgPool, user_id: i64, requested: Option<&str>) -> Result<String, sqlx::Error> {
+    if let Some(timezone) = requested {
+        if !user_repository::timezone_exists(pool, timezone).await? {
+            return Err(sqlx::Error::Io(std::io::Error::other("TIMEZONE_INVALID")));
+        }
+        return Ok(timezone.to_string());
+    }
+
+    match user_repository::find_by_id(pool, user_id).await? {
+        Some(user) => Ok(user.timezone),
+        None => Err(sqlx::Error::RowNotFound),
+    }
+}
+
 pub async fn admin_soft_delete_user(pool: &PgPool, user_id: i64) -> Result<Option<User>, sqlx::Error> {
     let user = user_repository::soft_delete_by_id(pool, user_id).await?;
     if user.is_some() {
@@ -68,8 +94,9 @@ pub async fn login_user(pool: &PgPool, email: &str, password: &str) -> Result<Lo
     
     let refresh_exp = DateTime::from_timestamp(refresh_exp_timestamp, 0).unwrap_or(now + Duration::days(30));
     let refresh_token_hash = hash_refresh_token(&refresh_token);
+    let family_id = Uuid::new_v4().simple().to_string();
 
-    user_repository::save_refresh_token(pool, user.id, &refresh_token_hash, refresh_exp).await?;
+    user_repository::save_refresh_token(pool, user.id, &refresh_token_hash, &family_id, refresh_exp).await?;
 
     Ok(LoginResponse {
         access_token,
@@ -77,6 +104,9 @@ pub async fn login_user(pool: &PgPool, email: &str, password: &str) -> Result<Lo
     })
 }
 
+/// Exchanges a refresh token for a new access token and a new refresh token.
+/// The presented token is revoked; presenting it again is reported as
+/// `REFRESH_TOKEN_REUSED` and revokes every token descending from the same login.
 pub async fn refresh_access_token(
     pool: &PgPool,
     refresh_token: &str,
@@ -84,13 +114,6 @@ pub async fn refresh_access_token(
     let token_data = decode_refresh_token(refresh_token)
         .map_err(|_| sqlx::Error::Io(std::io::Error::other("REFRESH_TOKEN_INVALID")))?;
 
-    let refresh_token_hash = hash_refresh_token(refresh_token);
-
-    let is_active = user_repository::is_refresh_token_active(pool, &refresh_token_hash).await?;
-    if !is_active {
-        return Err(sqlx::Error::Io(std::io::Error::other("REFRESH_TOKEN_INVALID")));
-    }
-
     let user_id = token_data
         .sub
         .parse::<i64>()
@@ -103,10 +126,41 @@ pub async fn refresh_access_token(
         None => return Err(sqlx::Error::Io(std::io::Error::other("REFRESH_TOKEN_INVALID"))),
     }
 
+    let now = Utc::now();
+    let (new_refresh_token, refresh_exp_timestamp) = encode_refresh_token(token_data.sub.clone(), token_data.role.clone(), now.timestamp())
+        .map_err(|e| sqlx::Error::Io(std::io::Error::other(format!("Refresh JWT encode error: {e}"))))?;
+    let refresh_exp = DateTime::from_timestamp(refresh_exp_timestamp, 0).unwrap_or(now + Duration::days(30));
+
+    let rotation = user_repository::rotate_refresh_token(
+        pool,
+        user_id,
+        &hash_refresh_token(refresh_token),
+        &hash_refresh_token(&new_refresh_token),
+        refresh_exp,
+    )
+    .await?;
+
+    match rotation {
+        RefreshRotation::Rotated => {}
+        RefreshRotation::Reused { user_id, family_id, revoked } => {
+            tracing::warn!(
+                "Refresh token reuse for user {}: revoked {} tokens of family {}",
+                user_id,
+                revoked,
+                family_id
+            );
+            return Err(sqlx::Error::Io(std::io::Error::other("REFRESH_TOKEN_REUSED")));
+        }
+        RefreshRotation::Invalid => return Err(sqlx::Error::Io(std::io::Error::other("REFRESH_TOKEN_INVALID"))),
+    }
+
     let access_token = encode_access_token(token_data.sub, token_data.role)
         .map_err(|e| sqlx::Error::Io(std::io::Error::other(format!("Access JWT encode error: {e}"))))?;
 
-    Ok(RefreshTokenResponse { access_token })
+    Ok(RefreshTokenResponse {
+        access_token,
+        refresh_token: new_refresh_token,
+    })
 }
 
 pub async fn logout_user(pool: &PgPool, refresh_token: &str) -> Result<(), sqlx::Error> {
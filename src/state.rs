@@ -1,14 +1,31 @@
 use sqlx::PgPool;
 use deadpool_redis::Pool;
+use crate::services::click_event_service::ClickEventWriter;
+use crate::services::link_service::CodeStrategy;
+use crate::services::live_click_service::LiveClickHub;
+use crate::services::retention_service::RetentionPolicy;
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: PgPool,
     pub redis: Pool,
+    pub click_events: ClickEventWriter,
+    pub live_clicks: LiveClickHub,
+    /// Policy the retention job runs with; `None` when the job is turned off.
+    pub retention: Option<RetentionPolicy>,
+    /// How codes are generated for links without an alias, read once at startup.
+    pub code_strategy: CodeStrategy,
 }
 
 impl AppState {
-    pub fn new(db: PgPool, redis: Pool) -> Self {
-        Self { db, redis }
+    pub fn new(
+        db: PgPool,
+        redis: Pool,
+        click_events: ClickEventWriter,
+        live_clicks: LiveClickHub,
+        retention: Option<RetentionPolicy>,
+        code_strategy: CodeStrategy,
+    ) -> Self {
+        Self { db, redis, click_events, live_clicks, retention, code_strategy }
     }
 }
@@ -0,0 +1,95 @@
+use axum::http::{HeaderMap, Method};
+
+/// Lower-case `User-Agent` fragments of clients that are not a person following
+/// the link: link unfurlers, search crawlers, uptime monitors and HTTP libraries.
+/// Entries must stay specific enough not to match a regular browser.
+const BOT_SIGNATURES: &[&str] = &[
+    // Link previews in chat apps and social networks
+    "slackbot",
+    "slack-imgproxy",
+    "facebookexternalhit",
+    "facebookcatalog",
+    "meta-externalagent",
+    "twitterbot",
+    "linkedinbot",
+    "discordbot",
+    "telegrambot",
+    "whatsapp/",
+    "skypeuripreview",
+    "redditbot",
+    "pinterestbot",
+    "embedly",
+    "iframely",
+    "vkshare",
+    "quora link preview",
+    "google-pagerenderer",
+    // Search engines and SEO crawlers
+    "googlebot",
+    "google-inspectiontool",
+    "bingbot",
+    "applebot",
+    "duckduckbot",
+    "yandexbot",
+    "baiduspider",
+    "petalbot",
+    "ahrefsbot",
+    "semrushbot",
+    "mj12bot",
+    "slurp",
+    // Uptime and synthetic monitoring
+    "uptimerobot",
+    "pingdom",
+    "statuscake",
+    "site24x7",
+    "betteruptime",
+    "datadogsynthetics",
+    "newrelicpinger",
+    "freshping",
+    "headlesschrome",
+    "lighthouse",
+    // Scripts and HTTP libraries
+    "curl/",
+    "wget/",
+    "python-requests",
+    "python-urllib",
+    "go-http-client",
+    "java/",
+    "apache-httpclient",
+    "libwww-perl",
+    "node-fetch",
+    "axios/",
+    "postmanruntime",
+    // Generic markers most other crawlers carry
+    "crawler",
+    "spider",
+    "bot/",
+    "bot;",
+    "bot)",
+];
+
+/// Whether a redirect request comes from something other than a person clicking:
+/// a `HEAD` request, a browser prefetch, a missing `User-Agent` or a known bot signature.
+pub fn is_bot_request(method: &Method, headers: &HeaderMap, user_agent: Option<&str>) -> bool {
+    *method == Method::HEAD || is_prefetch(headers) || user_agent.is_none_or(is_bot_user_agent)
+}
+
+pub fn is_bot_user_agent(user_agent: &str) -> bool {
+    let lower = user_agent.to_ascii_lowercase();
+    BOT_SIGNATURES.iter().any(|signature| lower.contains(signature))
+}
+
+/// Speculative loads: `Sec-Purpose`/`Purpose: prefetch` (Chromium), `X-Moz: prefetch`
+/// (Firefox) and `X-Purpose: preview` (Safari).
+fn is_prefetch(headers: &HeaderMap) -> bool {
+    let header_contains = |name: &str, needle: &str| {
+        headers
+            .get(name)
+            .and_then(|h| h.to_str().ok())
+            .is_some_and(|v| v.to_ascii_lowercase().contains(needle))
+    };
+
+    header_contains("sec-purpose", "prefetch")
+        || header_contains("purpose", "prefetch")
+        || header_contains("x-moz", "prefetch")
+        || header_contains("x-purpose", "preview")
+}
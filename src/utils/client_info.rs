@@ -0,0 +1,75 @@
+use std::net::{IpAddr, SocketAddr};
+
+use axum::http::{header, HeaderMap};
+
+const MAX_USER_AGENT_LEN: usize = 512;
+
+/// Client address, preferring proxy headers over the socket peer the same way
+/// the rate limiter does.
+pub fn client_ip(headers: &HeaderMap, peer: Option<SocketAddr>) -> Option<IpAddr> {
+    headers
+        .get("x-forwarded-for")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|v| v.split(',').next())
+        .and_then(|v| v.trim().parse().ok())
+        .or_else(|| {
+            headers
+                .get("x-real-ip")
+                .and_then(|h| h.to_str().ok())
+                .and_then(|v| v.trim().parse().ok())
+        })
+        .or_else(|| peer.map(|addr| addr.ip()))
+}
+
+/// Sites that send visitors from several hosts, reported under one name.
+const REFERRER_ALIASES: &[(&str, &str)] = &[
+    ("l.facebook.com", "facebook.com"),
+    ("lm.facebook.com", "facebook.com"),
+    ("l.instagram.com", "instagram.com"),
+    ("t.co", "twitter.com"),
+    ("x.com", "twitter.com"),
+    ("out.reddit.com", "reddit.com"),
+    ("com.google.android.gm", "mail.google.com"),
+];
+
+/// Host of the `Referer` header, normalized with `normalize_referrer_host`.
+pub fn referrer_host(headers: &HeaderMap) -> Option<String> {
+    let referrer = headers.get(header::REFERER)?.to_str().ok()?;
+    let host = url::Url::parse(referrer).ok()?.host_str()?.to_string();
+    Some(normalize_referrer_host(&host)).filter(|host| host.len() <= 255)
+}
+
+/// Lower-cases a referrer host, drops `www.` and `m.` prefixes and folds known
+/// aliases, so `https://m.facebook.com/...` and `l.facebook.com` both count as `facebook.com`.
+pub fn normalize_referrer_host(host: &str) -> String {
+    let host = host.trim().trim_end_matches('.').to_ascii_lowercase();
+    let host = host
+        .strip_prefix("www.")
+        .or_else(|| host.strip_prefix("m."))
+        .unwrap_or(&host);
+    REFERRER_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == host)
+        .map_or(host, |(_, canonical)| canonical)
+        .to_string()
+}
+
+pub fn user_agent(headers: &HeaderMap) -> Option<String> {
+    let user_agent = headers.get(header::USER_AGENT)?.to_str().ok()?.trim();
+    if user_agent.is_empty() {
+        return None;
+    }
+    Some(user_agent.chars().take(MAX_USER_AGENT_LEN).collect())
+}
+
+/// Two-letter country code from a CDN header such as Cloudflare's `CF-IPCountry`.
+/// There is no GeoIP lookup of our own yet, so without the header this is `None`.
+pub fn country(headers: &HeaderMap) -> Option<String> {
+    let code = headers.get("cf-ipcountry")?.to_str().ok()?.trim().to_ascii_uppercase();
+    // Cloudflare uses XX for unknown and T1 for Tor.
+    if code.len() == 2 && code.chars().all(|c| c.is_ascii_uppercase()) && code != "XX" {
+        Some(code)
+    } else {
+        None
+    }
+}
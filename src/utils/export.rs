@@ -0,0 +1,108 @@
+use serde::Serialize;
+
+/// File formats the export endpoints can stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Ndjson,
+}
+
+impl ExportFormat {
+    pub fn parse(value: Option<&str>) -> Option<Self> {
+        match value {
+            None | Some("csv") => Some(ExportFormat::Csv),
+            Some("ndjson") => Some(ExportFormat::Ndjson),
+            Some(_) => None,
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Ndjson => "ndjson",
+        }
+    }
+}
+
+/// A row that can be written as a CSV record or as one NDJSON line.
+pub trait ExportRow: Serialize {
+    const CSV_HEADER: &'static [&'static str];
+
+    /// Values in `CSV_HEADER` order, not yet escaped.
+    fn csv_values(&self) -> Vec<String>;
+}
+
+/// Header line for CSV, nothing for NDJSON.
+pub fn header<T: ExportRow>(format: ExportFormat) -> Option<String> {
+    match format {
+        ExportFormat::Csv => Some(format!("{}\r\n", T::CSV_HEADER.join(","))),
+        ExportFormat::Ndjson => None,
+    }
+}
+
+/// One encoded line, terminator included.
+pub fn encode<T: ExportRow>(format: ExportFormat, row: &T) -> Result<String, serde_json::Error> {
+    match format {
+        ExportFormat::Csv => {
+            let values: Vec<String> = row.csv_values().iter().map(|v| csv_field(v)).collect();
+            Ok(format!("{}\r\n", values.join(",")))
+        }
+        ExportFormat::Ndjson => Ok(format!("{}\n", serde_json::to_string(row)?)),
+    }
+}
+
+/// Quotes a CSV field when needed (RFC 4180). Any field starting with `=`, `+`,
+/// `-`, `@`, a tab or a carriage return is prefixed with `'` so spreadsheets do
+/// not run it as a formula; plain numbers such as `-5` are left alone.
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) && value.parse::<f64>().is_err() {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_fields_are_left_alone() {
+        assert_eq!(csv_field("https://example.com/a?b=c"), "https://example.com/a?b=c");
+        assert_eq!(csv_field(""), "");
+        assert_eq!(csv_field("-5"), "-5");
+        assert_eq!(csv_field("+1.5"), "+1.5");
+    }
+
+    #[test]
+    fn special_characters_are_quoted() {
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_field("cr\rhere"), "\"cr\rhere\"");
+    }
+
+    #[test]
+    fn formula_prefixes_are_neutralized() {
+        assert_eq!(csv_field("=1+1"), "'=1+1");
+        assert_eq!(csv_field("+cmd"), "'+cmd");
+        assert_eq!(csv_field("-2+3"), "'-2+3");
+        assert_eq!(csv_field("@SUM(A1)"), "'@SUM(A1)");
+        assert_eq!(csv_field("\t=1+1"), "'\t=1+1");
+        assert_eq!(csv_field("\r=1+1"), "\"'\r=1+1\"");
+        assert_eq!(csv_field("=HYPERLINK(\"x\",\"y\")"), "\"'=HYPERLINK(\"\"x\"\",\"\"y\"\")\"");
+    }
+}
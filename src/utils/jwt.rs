@@ -38,6 +38,7 @@ pub fn encode_access_token(sub: String, role: String) -> Result<String, AppError
         iat: now.timestamp() as usize,
         exp: expire.timestamp() as usize,
         jti: None,
+        scopes: None,
     };
 
     encode(
@@ -64,6 +65,7 @@ pub fn encode_refresh_token(sub: String, role: String, created_at: i64) -> Resul
         iat: now.timestamp() as usize,
         exp: exp_timestamp as usize,
         jti: Some(Uuid::new_v4().to_string()),
+        scopes: None,
     };
 
     let token = encode(
@@ -1,2 +1,7 @@
 pub mod jwt;
-pub mod validation;
\ No newline at end of file
+pub mod validation;
+pub mod client_info;
+pub mod user_agent;
+pub mod bot_detection;
+pub mod export;
+pub mod webhook;
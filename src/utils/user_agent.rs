@@ -0,0 +1,134 @@
+use crate::utils::bot_detection;
+
+/// What the analytics breakdowns need to know about a `User-Agent` string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserAgentInfo {
+    pub browser: &'static str,
+    /// Major version of the browser, when it could be read.
+    pub browser_version: Option<String>,
+    pub os: &'static str,
+    pub device: DeviceClass,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceClass {
+    Desktop,
+    Mobile,
+    Tablet,
+    Bot,
+    Unknown,
+}
+
+impl DeviceClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeviceClass::Desktop => "desktop",
+            DeviceClass::Mobile => "mobile",
+            DeviceClass::Tablet => "tablet",
+            DeviceClass::Bot => "bot",
+            DeviceClass::Unknown => "unknown",
+        }
+    }
+}
+
+/// Browser or OS family of a user agent the parser does not recognise.
+pub const OTHER: &str = "Other";
+
+/// Browser families, most specific first: Edge, Opera, Samsung and in-app
+/// browsers all carry `Chrome/` and `Safari/` tokens as well.
+const BROWSERS: &[(&str, &[&str])] = &[
+    ("Edge", &["Edg/", "EdgA/", "EdgiOS/", "Edge/"]),
+    ("Opera", &["OPR/", "OPiOS/", "Opera/"]),
+    ("Samsung Internet", &["SamsungBrowser/"]),
+    ("Yandex", &["YaBrowser/"]),
+    ("Coc Coc", &["coc_coc_browser/"]),
+    ("Zalo", &["Zalo/", "ZaloTheme"]),
+    ("Facebook", &["FBAV/", "FBAN/", "FB_IAB/"]),
+    ("Instagram", &["Instagram "]),
+    ("Firefox", &["Firefox/", "FxiOS/"]),
+    ("Chrome", &["CriOS/", "Chrome/"]),
+    ("Safari", &["Version/"]),
+    ("Internet Explorer", &["MSIE ", "Trident/"]),
+];
+
+pub fn parse(user_agent: &str) -> UserAgentInfo {
+    let (browser, browser_version) = parse_browser(user_agent);
+    let os = parse_os(user_agent);
+    let device = if bot_detection::is_bot_user_agent(user_agent) {
+        DeviceClass::Bot
+    } else {
+        parse_device(user_agent, os)
+    };
+
+    UserAgentInfo {
+        browser,
+        browser_version,
+        os,
+        device,
+    }
+}
+
+fn parse_browser(user_agent: &str) -> (&'static str, Option<String>) {
+    for (family, tokens) in BROWSERS {
+        for token in *tokens {
+            if let Some(position) = user_agent.find(token) {
+                // `Version/` only means Safari when the UA is a WebKit Safari one.
+                if *family == "Safari" && !user_agent.contains("Safari/") {
+                    continue;
+                }
+                let version = if *token == "Trident/" {
+                    version_after(user_agent, "rv:")
+                } else {
+                    major_version(&user_agent[position + token.len()..])
+                };
+                return (family, version);
+            }
+        }
+    }
+    (OTHER, None)
+}
+
+fn parse_os(user_agent: &str) -> &'static str {
+    if user_agent.contains("Windows Phone") {
+        "Windows Phone"
+    } else if user_agent.contains("Windows") {
+        "Windows"
+    } else if user_agent.contains("Android") {
+        "Android"
+    } else if user_agent.contains("iPad") {
+        "iPadOS"
+    } else if user_agent.contains("iPhone") || user_agent.contains("iPod") {
+        "iOS"
+    } else if user_agent.contains("CrOS") {
+        "ChromeOS"
+    } else if user_agent.contains("Mac OS X") || user_agent.contains("Macintosh") {
+        "macOS"
+    } else if user_agent.contains("Linux") || user_agent.contains("X11") {
+        "Linux"
+    } else {
+        OTHER
+    }
+}
+
+fn parse_device(user_agent: &str, os: &str) -> DeviceClass {
+    if user_agent.contains("iPad") || user_agent.contains("Tablet") {
+        return DeviceClass::Tablet;
+    }
+    match os {
+        "Android" if !user_agent.contains("Mobile") => DeviceClass::Tablet,
+        "Android" | "iOS" | "Windows Phone" => DeviceClass::Mobile,
+        "Windows" | "macOS" | "Linux" | "ChromeOS" => DeviceClass::Desktop,
+        _ if user_agent.contains("Mobi") => DeviceClass::Mobile,
+        _ => DeviceClass::Unknown,
+    }
+}
+
+fn version_after(user_agent: &str, token: &str) -> Option<String> {
+    let position = user_agent.find(token)?;
+    major_version(&user_agent[position + token.len()..])
+}
+
+fn major_version(rest: &str) -> Option<String> {
+    let major: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    (!major.is_empty()).then_some(major)
+}
@@ -15,8 +15,7 @@ pub fn validate_password(password: &str) -> bool {
 }
 
 pub fn validate_username(username: &str) -> bool {
-    let len = username.len();
-    len >= 3 && len <= 50
+    (3..=50).contains(&username.len())
 }
 
 pub fn validate_url(input: &str) -> bool {
@@ -29,3 +28,24 @@ pub fn validate_url(input: &str) -> bool {
 pub fn validate_title(title: &str) -> bool {
     !title.is_empty() && title.len() <= 255
 }
+
+
+const RESERVED_ALIASES: &[&str] = &[
+    "admin", "alerts", "api-docs", "api-keys", "docs", "health", "links", "login", "logout", "notifications", "refresh", "register", "tags", "users",
+];
+
+pub fn validate_alias(alias: &str) -> bool {
+    (3..=16).contains(&alias.len())
+        && alias.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        && !alias.starts_with(['-', '_'])
+        && !RESERVED_ALIASES.contains(&alias.to_ascii_lowercase().as_str())
+}
+
+pub fn validate_tag_name(name: &str) -> bool {
+    let name = name.trim();
+    !name.is_empty() && name.chars().count() <= 50
+}
+
+pub fn validate_tag_color(color: &str) -> bool {
+    color.len() == 7 && color.starts_with('#') && color[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
@@ -0,0 +1,127 @@
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::{Arc, LazyLock};
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use url::{Host, Url};
+
+/// Optional `ALERT_WEBHOOK_ALLOWED_HOSTS`: comma separated hosts webhooks may
+/// target; a host also allows its subdomains. Unset or empty allows any public host.
+static ALLOWED_HOSTS: LazyLock<Vec<String>> = LazyLock::new(|| {
+    std::env::var("ALERT_WEBHOOK_ALLOWED_HOSTS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|host| host.trim().trim_end_matches('.').to_ascii_lowercase())
+        .filter(|host| !host.is_empty())
+        .collect()
+});
+
+/// Checks that a webhook URL points at the public internet: its host must be
+/// allowed and every address it resolves to must be public. Run when a rule is
+/// saved and again before each delivery.
+pub async fn check_target(url: &str) -> Result<(), String> {
+    let url = Url::parse(url).map_err(|_| "webhook_url must be a valid http(s) URL".to_string())?;
+    let host = url.host().ok_or_else(|| "webhook_url must have a host".to_string())?;
+
+    let name = match &host {
+        Host::Domain(domain) => domain.trim_end_matches('.').to_ascii_lowercase(),
+        Host::Ipv4(ip) => ip.to_string(),
+        Host::Ipv6(ip) => ip.to_string(),
+    };
+    if !is_allowed_host(&name) {
+        return Err(format!("webhook_url host {} is not in ALERT_WEBHOOK_ALLOWED_HOSTS", name));
+    }
+
+    let addrs: Vec<IpAddr> = match host {
+        Host::Ipv4(ip) => vec![IpAddr::V4(ip)],
+        Host::Ipv6(ip) => vec![IpAddr::V6(ip)],
+        Host::Domain(domain) => tokio::net::lookup_host((domain, url.port_or_known_default().unwrap_or(443)))
+            .await
+            .map_err(|_| format!("webhook_url host {} does not resolve", name))?
+            .map(|addr| addr.ip())
+            .collect(),
+    };
+    if addrs.is_empty() || !addrs.iter().copied().all(is_public_ip) {
+        return Err(format!("webhook_url host {} is not a public address", name));
+    }
+    Ok(())
+}
+
+fn is_allowed_host(host: &str) -> bool {
+    ALLOWED_HOSTS.is_empty()
+        || ALLOWED_HOSTS
+            .iter()
+            .any(|allowed| host == allowed || host.strip_suffix(allowed.as_str()).is_some_and(|rest| rest.ends_with('.')))
+}
+
+/// Whether an address is reachable on the public internet, i.e. not loopback,
+/// private, link-local (cloud metadata lives there), unspecified or reserved.
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => {
+            if let Some(mapped) = ip.to_ipv4_mapped() {
+                return is_public_ipv4(mapped);
+            }
+            let segments = ip.segments();
+            // NAT64 addresses carry an IPv4 address in their last 32 bits.
+            if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+                let [a, b] = segments[6].to_be_bytes();
+                let [c, d] = segments[7].to_be_bytes();
+                return is_public_ipv4(Ipv4Addr::new(a, b, c, d));
+            }
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                || ip.is_unique_local()
+                || ip.is_unicast_link_local()
+                || segments[0] == 0x2001 && segments[1] == 0x0db8)
+        }
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        || a == 0
+        || a == 100 && (64..128).contains(&b)
+        || a == 192 && b == 0 && c == 0
+        || a == 198 && (18..20).contains(&b)
+        || a >= 240)
+}
+
+/// DNS resolver for webhook deliveries that drops every non-public address,
+/// so a host cannot be re-pointed at an internal one after `check_target`.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| is_public_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} has no public address", host).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Client for webhook deliveries: public addresses only, no proxy (it would
+/// resolve names itself) and no redirects (they could point anywhere).
+pub fn client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .dns_resolver(Arc::new(PublicResolver))
+        .redirect(reqwest::redirect::Policy::none())
+        .no_proxy()
+        .build()
+        .expect("Failed to build the webhook HTTP client")
+}